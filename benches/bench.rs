@@ -1,25 +1,59 @@
-use aoc21_rs::{day06, day07};
+use aoc21_rs::*;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
-fn multipart(c: &mut Criterion) {
-    c.bench_function("day 06", |b| {
-        let input = day06::parse_input(include_str!("../inputs/day06.txt"));
+fn bench_day<S: Solution>(c: &mut Criterion, inputs: &Inputs) {
+    let text = match inputs.day(S::DAY) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!(
+                "skipping day {:02}: cannot read {}: {}",
+                S::DAY,
+                inputs.path(&inputs::day_name(S::DAY)).display(),
+                e
+            );
+            return;
+        }
+    };
+    let mut group = c.benchmark_group(format!("day {:02}", S::DAY));
 
-        b.iter(|| {
-            day06::part_1(black_box(input.clone()));
-            day06::part_2(black_box(input.clone()));
-        });
+    group.bench_function("parse", |b| {
+        b.iter(|| S::parse_input(black_box(&text)).unwrap());
     });
 
-    c.bench_function("day 07", |b| {
-        let input = day07::parse_input(include_str!("../inputs/day07.txt"));
+    let input = S::parse_input(&text).unwrap();
 
-        b.iter(|| {
-            day07::part_1(black_box(&input));
-            day07::part_2(black_box(&input));
-        });
+    group.bench_function("part 1", |b| {
+        b.iter(|| S::part_1(black_box(&input)));
     });
+
+    group.bench_function("part 2", |b| {
+        b.iter(|| S::part_2(black_box(&input)));
+    });
+
+    group.finish();
+}
+
+macro_rules! days {
+    ($($day:ident),*) => {
+        fn all_days(c: &mut Criterion) {
+            let inputs = Inputs::from_env_or(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs"));
+
+            $(
+                bench_day::<$day::Solver>(c, &inputs);
+            )*
+        }
+    };
 }
 
-criterion_group!(benches, multipart);
+days!(
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25
+);
+
+criterion_group! {
+    name = benches;
+    // The slowest days take seconds per iteration, keep the whole suite within a few minutes
+    config = Criterion::default().sample_size(10);
+    targets = all_days
+}
 criterion_main!(benches);
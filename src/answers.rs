@@ -0,0 +1,77 @@
+//! Expected answers for puzzle inputs, as listed in `inputs/answers.txt`.
+//!
+//! Every line that isn't blank or a `#` comment holds the name of an input file, without the
+//! `.txt` extension and starting with `dayNN`, followed by the answers to both parts. A `?` marks
+//! an answer that isn't known yet.
+
+use crate::Part;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answers {
+    pub input: String,
+    pub day: u8,
+    parts: [Option<String>; 2],
+}
+
+impl Answers {
+    /// The expected answer to `part`, formatted like [`crate::Answer`], if it's known.
+    pub fn expected(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.parts[0].as_deref(),
+            Part::Two => self.parts[1].as_deref(),
+        }
+    }
+}
+
+pub fn parse(text: &str) -> Result<Vec<Answers>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| {
+            let (input, p1, p2) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [input, p1, p2] => (input, p1, p2),
+                _ => {
+                    return Err(format!(
+                        "line {}: expected an input name followed by two answers",
+                        i + 1
+                    ))
+                }
+            };
+
+            let day = input
+                .strip_prefix("day")
+                .and_then(|name| name.get(..2))
+                .and_then(|day| day.parse().ok())
+                .filter(|day| (1..=25).contains(day))
+                .ok_or_else(|| format!("line {}: `{}` is not named after a day", i + 1, input))?;
+
+            let answer = |s: &str| Some(s.to_string()).filter(|s| s != "?");
+
+            Ok(Answers {
+                input: input.to_string(),
+                day,
+                parts: [answer(p1), answer(p2)],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_answers() {
+        let answers = parse("# comment\n\nday01 7 5\nday13-alice 17 ?\n").unwrap();
+
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[1].input, "day13-alice");
+        assert_eq!(answers[1].day, 13);
+        assert_eq!(answers[1].expected(Part::One), Some("17"));
+        assert_eq!(answers[1].expected(Part::Two), None);
+
+        assert!(parse("day01 7").is_err());
+        assert!(parse("day26 7 5").is_err());
+        assert!(parse("alice 7 5").is_err());
+    }
+}
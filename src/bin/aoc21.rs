@@ -0,0 +1,227 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    process,
+};
+
+use aoc21_rs::{
+    inputs, json,
+    runner::{self, Table},
+    Inputs, Part,
+};
+
+const USAGE: &str = "\
+Usage: aoc21 [--part <1|2>] [--json] <DAY> [INPUT]
+       aoc21 [--part <1|2>] [--json] [--jobs <N>] all
+
+Runs the solution for DAY (1-25) on INPUT and prints the answers.
+If INPUT is `-`, the puzzle input is read from stdin. If it's omitted, the
+puzzle input is read from `dayNN.txt` in the directory named by
+$AOC21_INPUTS (`inputs` by default).
+
+With `all`, runs every day on its input from that directory and prints how
+long parsing and solving each part took.
+
+Options:
+    -p, --part <1|2>    only solve the given part
+    -j, --jobs <N>      run up to N days at once with `all` (default: 1)
+        --json          print answers and timings as JSON records
+    -h, --help          print this message";
+
+struct Args {
+    parts: Vec<Part>,
+    json: bool,
+    command: Command,
+}
+
+enum Command {
+    Day { day: u8, input: Source },
+    All { jobs: usize },
+}
+
+/// Where the puzzle input comes from.
+enum Source {
+    File(String),
+    Stdin,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = env::args().skip(1);
+    let mut positional = Vec::new();
+    let mut parts = vec![Part::One, Part::Two];
+    let mut jobs = None;
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "-p" | "--part" => {
+                parts = match args.next().as_deref() {
+                    Some("1") => vec![Part::One],
+                    Some("2") => vec![Part::Two],
+                    Some(part) => return Err(format!("invalid part `{}`", part)),
+                    None => return Err("missing value for `--part`".to_string()),
+                }
+            }
+            "-j" | "--jobs" => {
+                jobs = match args.next() {
+                    Some(n) => match n.parse() {
+                        Ok(n) if n > 0 => Some(n),
+                        _ => return Err(format!("invalid number of jobs `{}`", n)),
+                    },
+                    None => return Err("missing value for `--jobs`".to_string()),
+                }
+            }
+            "--json" => json = true,
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+
+    let command = match positional.next().as_deref() {
+        Some("all") => Command::All {
+            jobs: jobs.unwrap_or(1),
+        },
+        Some(day) => {
+            let day = day.parse().map_err(|_| format!("invalid day `{}`", day))?;
+
+            let input = match positional.next() {
+                Some(path) if path != "-" => Source::File(path),
+                Some(_) => Source::Stdin,
+                None => {
+                    let inputs = Inputs::default();
+                    Source::File(inputs.path(&inputs::day_name(day)).display().to_string())
+                }
+            };
+
+            if jobs.is_some() {
+                return Err("`--jobs` only applies to `all`".to_string());
+            }
+
+            Command::Day { day, input }
+        }
+        None => return Err("missing day".to_string()),
+    };
+
+    if let Some(arg) = positional.next() {
+        return Err(format!("unexpected argument `{}`", arg));
+    }
+
+    Ok(Args {
+        parts,
+        json,
+        command,
+    })
+}
+
+impl Source {
+    fn read(&self) -> io::Result<String> {
+        match self {
+            Source::File(path) => fs::read_to_string(path),
+            Source::Stdin => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                Ok(input)
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Source::File(path) => path,
+            Source::Stdin => "stdin",
+        }
+    }
+}
+
+fn run_day(number: u8, input: &Source, parts: &[Part], json: bool) {
+    let day = aoc21_rs::day(number).unwrap_or_else(|| {
+        eprintln!("error: no solution for day {}", number);
+        process::exit(2);
+    });
+
+    let text = input.read().unwrap_or_else(|e| {
+        eprintln!("error: cannot read {}: {}", input.name(), e);
+        process::exit(1);
+    });
+
+    let run = day.run_timed(&text, parts).unwrap_or_else(|e| {
+        eprintln!("error: invalid input in {}: {}", input.name(), e);
+        process::exit(1);
+    });
+
+    if json {
+        println!("{}", json::array(json::records(number, input.name(), &run)));
+        return;
+    }
+
+    for (part, answer) in run.parts.iter().map(|part| (part.part, &part.answer)) {
+        let label = match part {
+            Part::One => "Part 1",
+            Part::Two => "Part 2",
+        };
+
+        let answer = answer.to_string();
+        if answer.contains('\n') {
+            println!("{}:\n{}", label, answer);
+        } else {
+            println!("{}: {}", label, answer);
+        }
+    }
+}
+
+fn run_all(jobs: usize, parts: &[Part], json: bool) {
+    let inputs = Inputs::default();
+    let calendar = runner::run_all(&inputs, parts, jobs);
+
+    if json {
+        let path = |day| inputs.path(&inputs::day_name(day)).display().to_string();
+
+        println!(
+            "{}",
+            json::array(
+                calendar
+                    .runs()
+                    .flat_map(|(day, run)| json::records(day, &path(day), run))
+            )
+        );
+
+        // The table reports failures inline, but they'd be lost in here
+        for (day, run) in &calendar.days {
+            match run {
+                Err(e) if e.is_missing_input() => {
+                    eprintln!("skipping day {}: no {}", day, path(*day))
+                }
+                Err(e) => eprintln!("error: day {}: {}", day, e),
+                Ok(_) => (),
+            }
+        }
+    } else {
+        println!("{}", Table(&calendar));
+    }
+
+    // Missing inputs are only reported, but days that failed make the whole run fail
+    if calendar
+        .days
+        .iter()
+        .any(|(_, run)| matches!(run, Err(e) if !e.is_missing_input()))
+    {
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        process::exit(2);
+    });
+
+    match args.command {
+        Command::Day { day, input } => run_day(day, &input, &args.parts, args.json),
+        Command::All { jobs } => run_all(jobs, &args.parts, args.json),
+    }
+}
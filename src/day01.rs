@@ -1,9 +1,10 @@
-pub fn parse_input(input: &str) -> Vec<usize> {
-    input
-        .lines()
-        .map(str::parse::<usize>)
-        .collect::<Result<_, _>>()
-        .unwrap()
+use crate::{
+    parse::{self, lines, number},
+    ParseError, Solution,
+};
+
+pub fn parse_input(input: &str) -> Result<Vec<usize>, ParseError> {
+    parse::run(Solver::DAY, input, lines(number))
 }
 
 pub fn part_1(input: &[usize]) -> usize {
@@ -19,13 +20,48 @@ pub fn part_2(input: &[usize]) -> usize {
     )
 }
 
-crate::solutions!(
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 1;
+
+    type Input<'a> = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+199
+200
+208
+210
+200
+207
+240
+269
+260
+263";
+
+crate::solutions! {
     p1 => {
-        part_1(&parse_input(include_str!("../inputs/day01.txt"))),
-        1529
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        7
     },
     p2 => {
-        part_2(&parse_input(include_str!("../inputs/day01.txt"))),
-        1567
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        5
     }
-);
+}
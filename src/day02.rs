@@ -1,4 +1,9 @@
-use std::{num::ParseIntError, str::FromStr};
+use nom::{branch::alt, combinator::map, sequence::preceded};
+
+use crate::{
+    parse::{self, expect, lines, number, token, Expected, IResult},
+    ParseError, Solution,
+};
 
 pub enum Command {
     Forward(usize),
@@ -6,25 +11,19 @@ pub enum Command {
     Up(usize),
 }
 
-impl FromStr for Command {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.split_once(' ') {
-            Some(("forward", x)) => Ok(Command::Forward(x.parse()?)),
-            Some(("down", x)) => Ok(Command::Down(x.parse()?)),
-            Some(("up", x)) => Ok(Command::Up(x.parse()?)),
-            _ => unreachable!(),
-        }
-    }
+pub fn parse_input(input: &str) -> Result<Vec<Command>, ParseError> {
+    parse::run(Solver::DAY, input, lines(command))
 }
 
-pub fn parse_input(input: &str) -> Vec<Command> {
-    input
-        .lines()
-        .map(str::parse::<Command>)
-        .collect::<Result<_, _>>()
-        .unwrap()
+fn command(input: &str) -> IResult<'_, Command> {
+    expect(
+        Expected::OneOf(&["forward", "down", "up"]),
+        alt((
+            map(preceded(token("forward"), number), Command::Forward),
+            map(preceded(token("down"), number), Command::Down),
+            map(preceded(token("up"), number), Command::Up),
+        )),
+    )(input)
 }
 
 pub fn part_1(input: &[Command]) -> usize {
@@ -49,13 +48,44 @@ pub fn part_2(input: &[Command]) -> usize {
     pos * depth
 }
 
-crate::solutions!(
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 2;
+
+    type Input<'a> = Vec<Command>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2";
+
+crate::solutions! {
     p1 => {
-        part_1(&parse_input(include_str!("../inputs/day02.txt"))),
-        1698735
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        150
     },
     p2 => {
-        part_2(&parse_input(include_str!("../inputs/day02.txt"))),
-        1594785890
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        900
     }
-);
+}
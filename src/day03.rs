@@ -1,7 +1,23 @@
 use std::{cmp::Ordering, str};
 
-pub fn parse_input(input: &str) -> Vec<&str> {
-    input.lines().collect()
+use nom::{character::complete::one_of, combinator::recognize, multi::many1};
+
+use crate::{
+    parse::{self, expect, lines, Expected},
+    ParseError, Solution,
+};
+
+pub fn parse_input(input: &str) -> Result<Vec<&str>, ParseError> {
+    parse::run(Solver::DAY, input, |input| {
+        let binary = recognize(many1(one_of("01")));
+        let (rest, rows) = lines(expect(Expected::OneOf(&["0", "1"]), binary))(input)?;
+
+        if let Some(row) = rows.iter().find(|row| row.len() != rows[0].len()) {
+            return Err(parse::fail(row, Expected::Row(rows[0].len())));
+        }
+
+        Ok((rest, rows))
+    })
 }
 
 pub fn part_1(input: &[&str]) -> usize {
@@ -48,13 +64,50 @@ fn mcb(input: &[&str], pos: usize) -> Option<usize> {
     }
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 3;
+
+    type Input<'a> = Vec<&'a str>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010";
+
 crate::solutions! {
     p1 => {
-        part_1(&parse_input(include_str!("../inputs/day03.txt"))),
-        3242606
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        198
     },
     p2 => {
-        part_2(&parse_input(include_str!("../inputs/day03.txt"))),
-        4856080
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        230
     }
 }
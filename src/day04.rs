@@ -1,3 +1,15 @@
+use nom::{
+    character::complete::{space0, space1},
+    multi::separated_list1,
+    sequence::{preceded, separated_pair},
+};
+
+use crate::{
+    parse::{self, blank_line, lines, number, sections, token, Expected, IResult},
+    ParseError, Solution,
+};
+
+#[derive(Debug, Clone)]
 pub struct Board {
     entries: Vec<Vec<usize>>,
     marked: [[bool; 5]; 5],
@@ -28,37 +40,51 @@ impl Board {
     }
 }
 
-pub fn parse_input(input: &str) -> (Vec<usize>, Vec<Board>) {
-    let mut boards = input.split("\n\n");
-
-    let numbers = boards
-        .next()
-        .unwrap()
-        .split(',')
-        .map(str::parse::<usize>)
-        .collect::<Result<_, _>>()
-        .unwrap();
-
-    let boards = boards
-        .map(|board| Board {
-            entries: board
-                .lines()
-                .map(|line| {
-                    line.split_ascii_whitespace()
-                        .map(str::parse::<usize>)
-                        .collect::<Result<_, _>>()
-                        .unwrap()
-                })
-                .collect(),
+pub fn parse_input(input: &str) -> Result<(Vec<usize>, Vec<Board>), ParseError> {
+    parse::run(
+        Solver::DAY,
+        input,
+        separated_pair(
+            separated_list1(token(","), number),
+            blank_line,
+            sections(board),
+        ),
+    )
+}
+
+fn board(input: &str) -> IResult<'_, Board> {
+    let (rest, entries) = lines(row)(input)?;
+
+    if entries.len() != 5 {
+        return Err(parse::fail(
+            rest,
+            Expected::Description("a board of 5 rows"),
+        ));
+    }
+
+    Ok((
+        rest,
+        Board {
+            entries,
             marked: Default::default(),
-        })
-        .collect();
+        },
+    ))
+}
+
+fn row(input: &str) -> IResult<'_, Vec<usize>> {
+    let (rest, row) = preceded(space0, separated_list1(space1, number))(input)?;
+
+    if row.len() != 5 {
+        return Err(parse::fail(input, Expected::Row(5)));
+    }
 
-    (numbers, boards)
+    Ok((rest, row))
 }
 
-pub fn part_1((numbers, mut boards): (Vec<usize>, Vec<Board>)) -> usize {
-    for number in numbers {
+pub fn part_1((numbers, boards): &(Vec<usize>, Vec<Board>)) -> usize {
+    let mut boards = boards.clone();
+
+    for &number in numbers {
         for board in &mut boards {
             if board.mark(number) {
                 return number * board.score();
@@ -68,8 +94,10 @@ pub fn part_1((numbers, mut boards): (Vec<usize>, Vec<Board>)) -> usize {
     unreachable!()
 }
 
-pub fn part_2((numbers, mut boards): (Vec<usize>, Vec<Board>)) -> usize {
-    for number in numbers {
+pub fn part_2((numbers, boards): &(Vec<usize>, Vec<Board>)) -> usize {
+    let mut boards = boards.clone();
+
+    for &number in numbers {
         let mut i = 0;
         while i < boards.len() {
             if boards[i].mark(number) {
@@ -85,13 +113,57 @@ pub fn part_2((numbers, mut boards): (Vec<usize>, Vec<Board>)) -> usize {
     unreachable!()
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 4;
+
+    type Input<'a> = (Vec<usize>, Vec<Board>);
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day04.txt"))),
-        34506
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        4512
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day04.txt"))),
-        7686
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        1924
     }
 }
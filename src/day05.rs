@@ -1,5 +1,12 @@
 use std::collections::HashMap;
 
+use nom::{combinator::map, sequence::separated_pair};
+
+use crate::{
+    parse::{self, lines, number, token, Expected, IResult},
+    ParseError, Solution,
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Point(isize, isize);
 
@@ -21,19 +28,28 @@ impl Point {
     }
 }
 
-pub fn parse_input(input: &str) -> Vec<(Point, Point)> {
-    input
-        .lines()
-        .map(|line| {
-            let coords: Vec<_> = line
-                .split(" -> ")
-                .flat_map(|coord| coord.split(',').map(str::parse::<isize>))
-                .collect::<Result<_, _>>()
-                .unwrap();
-
-            (Point(coords[0], coords[1]), Point(coords[2], coords[3]))
-        })
-        .collect()
+pub fn parse_input(input: &str) -> Result<Vec<(Point, Point)>, ParseError> {
+    parse::run(Solver::DAY, input, lines(line))
+}
+
+/// A line of vents, which can only be horizontal, vertical or at 45 degrees.
+fn line(input: &str) -> IResult<'_, (Point, Point)> {
+    let (rest, (a, b)) = separated_pair(point, token("->"), point)(input)?;
+
+    if a.0 != b.0 && a.1 != b.1 && (a.0 - b.0).abs() != (a.1 - b.1).abs() {
+        return Err(parse::fail(
+            input,
+            Expected::Description("a horizontal, vertical or diagonal line"),
+        ));
+    }
+
+    Ok((rest, (a, b)))
+}
+
+fn point(input: &str) -> IResult<'_, Point> {
+    map(separated_pair(number, token(","), number), |(x, y)| {
+        Point(x, y)
+    })(input)
 }
 
 pub fn part_1(input: &[(Point, Point)]) -> usize {
@@ -60,13 +76,64 @@ pub fn part_2(input: &[(Point, Point)]) -> usize {
     map.values().filter(|&&n| n > 1).count()
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 5;
+
+    type Input<'a> = Vec<(Point, Point)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2";
+
 crate::solutions! {
     p1 => {
-        part_1(&parse_input(include_str!("../inputs/day05.txt"))),
-        4728
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        5
     },
     p2 => {
-        part_2(&parse_input(include_str!("../inputs/day05.txt"))),
-        17717
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        12
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_skewed_lines() {
+        assert_eq!(
+            parse_input("0,0 -> 1,1\n0,0 -> 1,3")
+                .unwrap_err()
+                .to_string(),
+            "day 5, line 2, column 1: expected a horizontal, vertical or diagonal line"
+        );
+        assert_eq!(parse_input("3,1 -> 3,1\n4,2 -> 1,5").unwrap().len(), 2);
     }
 }
@@ -1,41 +1,85 @@
 use std::collections::VecDeque;
 
-pub fn parse_input(input: &str) -> VecDeque<u64> {
-    VecDeque::from(
-        input
-            .split(',')
-            .map(str::parse::<usize>)
-            .fold([0; 9], |mut v, x| {
-                v[x.unwrap()] += 1;
-                v
-            }),
-    )
+use nom::multi::separated_list1;
+
+use crate::{
+    parse::{self, number, token, Expected, IResult},
+    ParseError, Solution,
+};
+
+pub fn parse_input(input: &str) -> Result<VecDeque<u64>, ParseError> {
+    let timers = parse::run(Solver::DAY, input, separated_list1(token(","), timer))?;
+
+    Ok(VecDeque::from(timers.into_iter().fold(
+        [0; 9],
+        |mut v, x| {
+            v[x] += 1;
+            v
+        },
+    )))
 }
 
-pub fn part_1(input: VecDeque<u64>) -> u64 {
+fn timer(input: &str) -> IResult<'_, usize> {
+    match number(input)? {
+        (rest, n @ 0..=8) => Ok((rest, n)),
+        _ => Err(parse::fail(
+            input,
+            Expected::Description("a timer between 0 and 8"),
+        )),
+    }
+}
+
+pub fn part_1(input: &VecDeque<u64>) -> u64 {
     generate(input, 80)
 }
 
-pub fn part_2(input: VecDeque<u64>) -> u64 {
+pub fn part_2(input: &VecDeque<u64>) -> u64 {
     generate(input, 256)
 }
 
-fn generate(mut input: VecDeque<u64>, gens: usize) -> u64 {
+fn generate(input: &VecDeque<u64>, gens: usize) -> u64 {
+    let mut timers = input.clone();
+
     for _ in 0..gens {
-        let spawn = input[0];
-        input.rotate_left(1);
-        input[6] += spawn;
+        let spawn = timers[0];
+        timers.rotate_left(1);
+        timers[6] += spawn;
+    }
+    timers.iter().sum()
+}
+
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 6;
+
+    type Input<'a> = VecDeque<u64>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
     }
-    input.iter().sum()
 }
 
+#[cfg(test)]
+const EXAMPLE: &str = "3,4,3,1,2";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day06.txt"))),
-        352195
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        5934
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day06.txt"))),
-        1600306001288
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        26984457539
     }
 }
@@ -1,9 +1,12 @@
-pub fn parse_input(input: &str) -> Vec<i32> {
-    input
-        .split(',')
-        .map(str::parse::<i32>)
-        .collect::<Result<_, _>>()
-        .unwrap()
+use nom::multi::separated_list1;
+
+use crate::{
+    parse::{self, number, token},
+    ParseError, Solution,
+};
+
+pub fn parse_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    parse::run(Solver::DAY, input, separated_list1(token(","), number))
 }
 
 pub fn part_1(input: &[i32]) -> u32 {
@@ -47,13 +50,38 @@ where
     }
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 7;
+
+    type Input<'a> = Vec<i32>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "16,1,2,0,4,2,7,1,2,14";
+
 crate::solutions! {
     p1 => {
-        part_1(&parse_input(include_str!("../inputs/day07.txt"))),
-        352331
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        37
     },
     p2 => {
-        part_2(&parse_input(include_str!("../inputs/day07.txt"))),
-        99266250
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        168
     }
 }
@@ -1,6 +1,18 @@
 use itertools::Itertools;
 use lazy_static::lazy_static;
 
+use nom::{
+    character::complete::{one_of, space0, space1},
+    combinator::{map, recognize},
+    multi::{many1, separated_list1},
+    sequence::{preceded, separated_pair},
+};
+
+use crate::{
+    parse::{self, expect, lines, token, Expected, IResult},
+    ParseError, Solution,
+};
+
 lazy_static! {
     static ref SEGMENT_STATE_BY_DIGIT: [&'static [u8]; 10] = [
         &b"abcefg"[..],
@@ -21,18 +33,27 @@ pub struct Input<'a> {
     output: Vec<&'a str>,
 }
 
-pub fn parse_input(input: &str) -> Vec<Input> {
-    input
-        .lines()
-        .map(|line| {
-            let (patterns, output) = line.split_once(" | ").unwrap();
+pub fn parse_input(input: &str) -> Result<Vec<Input<'_>>, ParseError> {
+    parse::run(
+        Solver::DAY,
+        input,
+        lines(map(
+            separated_pair(digits, token("|"), digits),
+            |(patterns, output)| Input { patterns, output },
+        )),
+    )
+}
 
-            Input {
-                patterns: patterns.split_ascii_whitespace().collect(),
-                output: output.split_ascii_whitespace().collect(),
-            }
-        })
-        .collect()
+fn digits(input: &str) -> IResult<'_, Vec<&str>> {
+    let segments = recognize(many1(one_of("abcdefg")));
+
+    preceded(
+        space0,
+        separated_list1(
+            space1,
+            expect(Expected::Description("segments from a to g"), segments),
+        ),
+    )(input)
 }
 
 pub fn part_1(inputs: &[Input]) -> usize {
@@ -82,13 +103,48 @@ fn decode(input: &Input) -> usize {
     unreachable!()
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 8;
+
+    type Input<'a> = Vec<Input<'a>>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
+
 crate::solutions! {
     p1 => {
-        part_1(&parse_input(include_str!("../inputs/day08.txt"))),
-        301
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        26
     },
     p2 => {
-        part_2(&parse_input(include_str!("../inputs/day08.txt"))),
-        908067
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        61229
     }
 }
@@ -1,30 +1,25 @@
 use std::collections::VecDeque;
 
+use crate::{
+    grid::Pos,
+    parse::{self, digit, grid},
+    Grid, ParseError, Solution,
+};
+
 pub struct Floor {
-    heightmap: Vec<u32>,
-    width: usize,
+    heightmap: Grid<u32>,
 }
 
 impl Floor {
-    pub fn higher_adjacent_positions(&self, i: usize) -> (Vec<usize>, bool) {
-        let (x, y) = ((i % self.width) as isize, (i / self.width) as isize);
-
-        let wi = self.width as isize;
-        let hi = (self.heightmap.len() / self.width) as isize;
-
-        let neighbours = [(x - 1, y), (x, y + 1), (x + 1, y), (x, y - 1)];
-        let score = self.heightmap[i];
+    pub fn higher_adjacent_positions(&self, pos: Pos) -> (Vec<Pos>, bool) {
+        let score = self.heightmap[pos];
 
         let mut higher = Vec::with_capacity(4);
         let mut minimum = true;
 
-        for (dx, dy) in neighbours {
-            if dx < 0 || dx >= wi || dy < 0 || dy >= hi {
-                continue;
-            }
-            let i = (dy * wi + dx) as usize;
-            if score < self.heightmap[i] {
-                higher.push(i);
+        for next in self.heightmap.neighbours4(pos) {
+            if score < self.heightmap[next] {
+                higher.push(next);
             } else {
                 minimum = false;
             }
@@ -33,52 +28,52 @@ impl Floor {
         (higher, minimum)
     }
 
-    pub fn risk_at(&self, i: usize) -> u32 {
-        if self.higher_adjacent_positions(i).1 {
-            self.heightmap[i] + 1
+    pub fn risk_at(&self, pos: Pos) -> u32 {
+        if self.higher_adjacent_positions(pos).1 {
+            self.heightmap[pos] + 1
         } else {
             0
         }
     }
 }
 
-pub fn parse_input(input: &str) -> Floor {
-    let width = input.find('\n').unwrap();
-
-    let heightmap = input
-        .lines()
-        .flat_map(|l| l.chars())
-        .map(|c| c.to_digit(10).unwrap())
-        .collect();
+pub fn parse_input(input: &str) -> Result<Floor, ParseError> {
+    let heightmap = parse::run(Solver::DAY, input, grid(digit))?;
 
-    Floor { heightmap, width }
+    Ok(Floor { heightmap })
 }
 
 pub fn part_1(floor: &Floor) -> u32 {
-    (0..floor.heightmap.len()).fold(0, |risk, i| risk + floor.risk_at(i))
+    floor
+        .heightmap
+        .positions()
+        .fold(0, |risk, pos| risk + floor.risk_at(pos))
 }
 
 pub fn part_2(floor: &Floor) -> usize {
-    let mut visited = vec![false; floor.heightmap.len()];
-
-    let mut baisins = (0..floor.heightmap.len()).fold(vec![], |mut baisins, i| {
-        if let Some(size) = find_baisin(floor, i, &mut visited) {
-            baisins.push(size);
-        }
-        baisins
-    });
+    let mut visited = floor.heightmap.map(|_| false);
+
+    let mut baisins = floor
+        .heightmap
+        .positions()
+        .fold(vec![], |mut baisins, pos| {
+            if let Some(size) = find_baisin(floor, pos, &mut visited) {
+                baisins.push(size);
+            }
+            baisins
+        });
 
     baisins.sort_by(|a, b| b.cmp(a));
     baisins[0] * baisins[1] * baisins[2]
 }
 
-fn find_baisin(floor: &Floor, i: usize, visited: &mut [bool]) -> Option<usize> {
+fn find_baisin(floor: &Floor, pos: Pos, visited: &mut Grid<bool>) -> Option<usize> {
     // Start looking from low points only
-    if floor.risk_at(i) == 0 {
+    if floor.risk_at(pos) == 0 {
         return None;
     }
 
-    let mut candidates = VecDeque::from_iter([i]);
+    let mut candidates = VecDeque::from_iter([pos]);
     let mut size = 0;
 
     while let Some(pos) = candidates.pop_front() {
@@ -89,9 +84,9 @@ fn find_baisin(floor: &Floor, i: usize, visited: &mut [bool]) -> Option<usize> {
         visited[pos] = true;
         size += 1;
 
-        for i in floor.higher_adjacent_positions(pos).0.into_iter() {
-            if floor.heightmap[i] != 9 {
-                candidates.push_back(i);
+        for next in floor.higher_adjacent_positions(pos).0.into_iter() {
+            if floor.heightmap[next] != 9 {
+                candidates.push_back(next);
             }
         }
     }
@@ -99,13 +94,43 @@ fn find_baisin(floor: &Floor, i: usize, visited: &mut [bool]) -> Option<usize> {
     Some(size)
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 9;
+
+    type Input<'a> = Floor;
+    type Part1 = u32;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+2199943210
+3987894921
+9856789892
+8767896789
+9899965678";
+
 crate::solutions! {
     p1 => {
-        part_1(&parse_input(include_str!("../inputs/day09.txt"))),
-        444
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        15
     },
     p2 => {
-        part_2(&parse_input(include_str!("../inputs/day09.txt"))),
-        1168440
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        1134
     }
 }
@@ -1,7 +1,20 @@
 use itertools::Itertools;
 
-pub fn parse_input(input: &str) -> Vec<&str> {
-    input.lines().collect()
+use nom::{character::complete::one_of, combinator::recognize, multi::many1};
+
+use crate::{
+    parse::{self, expect, lines, Expected},
+    ParseError, Solution,
+};
+
+pub fn parse_input(input: &str) -> Result<Vec<&str>, ParseError> {
+    let chunks = recognize(many1(one_of("()[]{}<>")));
+
+    parse::run(
+        Solver::DAY,
+        input,
+        lines(expect(Expected::Description("a bracket"), chunks)),
+    )
 }
 
 pub fn part_1(input: &[&str]) -> u32 {
@@ -14,10 +27,7 @@ pub fn part_1(input: &[&str]) -> u32 {
 pub fn part_2(input: &[&str]) -> u64 {
     let points = input
         .iter()
-        .filter_map(|line| match compile(line) {
-            Ok(score) => Some(score),
-            Err(_) => None,
-        })
+        .filter_map(|line| compile(line).ok())
         .sorted()
         .collect_vec();
 
@@ -63,13 +73,48 @@ const fn context_score(c: char) -> u64 {
     }
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 10;
+
+    type Input<'a> = Vec<&'a str>;
+    type Part1 = u32;
+    type Part2 = u64;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]";
+
 crate::solutions! {
     p1 => {
-        part_1(&parse_input(include_str!("../inputs/day10.txt"))),
-        362271
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        26397
     },
     p2 => {
-        part_2(&parse_input(include_str!("../inputs/day10.txt"))),
-        1698395182
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        288957
     }
 }
@@ -1,23 +1,25 @@
-pub fn parse_input(input: &str) -> Vec<u32> {
-    input
-        .lines()
-        .flat_map(|line| line.chars().map(|c| c.to_digit(10).unwrap()))
-        .collect()
+use crate::{
+    grid::{self, Pos},
+    parse::{self, digit, grid},
+    Grid, ParseError, Solution,
+};
+
+pub fn parse_input(input: &str) -> Result<Grid<u32>, ParseError> {
+    parse::run(Solver::DAY, input, grid(digit))
 }
 
-pub fn part_1(mut input: Vec<u32>) -> usize {
+pub fn part_1(input: &Grid<u32>) -> usize {
+    let mut input = input.clone();
     let mut total_flashes = 0;
 
     for _ in 0..100 {
-        let mut flashed = vec![false; input.len()];
+        let mut flashed = input.map(|_| false);
 
-        for x in 0..10 {
-            for y in 0..10 {
-                total_flashes += step(&mut input, &mut flashed, (x, y));
-            }
+        for pos in input.positions() {
+            total_flashes += step(&mut input, &mut flashed, pos);
         }
 
-        for (x, flashed) in input.iter_mut().zip(flashed.iter()) {
+        for (x, flashed) in input.cells_mut().iter_mut().zip(flashed.cells()) {
             if *flashed {
                 *x = 0;
             }
@@ -27,21 +29,20 @@ pub fn part_1(mut input: Vec<u32>) -> usize {
     total_flashes
 }
 
-pub fn part_2(mut input: Vec<u32>) -> usize {
+pub fn part_2(input: &Grid<u32>) -> usize {
+    let mut input = input.clone();
     for i in 1.. {
-        let mut flashed = vec![false; input.len()];
+        let mut flashed = input.map(|_| false);
 
-        for x in 0..10 {
-            for y in 0..10 {
-                step(&mut input, &mut flashed, (x, y));
-            }
+        for pos in input.positions() {
+            step(&mut input, &mut flashed, pos);
         }
 
-        if flashed.iter().all(|&f| f) {
+        if flashed.cells().iter().all(|&f| f) {
             return i;
         }
 
-        for (x, flashed) in input.iter_mut().zip(flashed.iter()) {
+        for (x, flashed) in input.cells_mut().iter_mut().zip(flashed.cells()) {
             if *flashed {
                 *x = 0;
             }
@@ -51,46 +52,66 @@ pub fn part_2(mut input: Vec<u32>) -> usize {
     unreachable!()
 }
 
-fn step(input: &mut [u32], flashed: &mut [bool], (x, y): (i32, i32)) -> usize {
-    let i = (y * 10 + x) as usize;
+fn step(input: &mut Grid<u32>, flashed: &mut Grid<bool>, pos: Pos) -> usize {
     let mut flashes = 0;
 
-    input[i] += 1;
-    if input[i] > 9 && !flashed[i] {
-        flashed[i] = true;
+    input[pos] += 1;
+    if input[pos] > 9 && !flashed[pos] {
+        flashed[pos] = true;
         flashes += 1;
 
-        let deltas = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-
-        for (dx, dy) in deltas {
-            let (xi, yi) = (x + dx, y + dy);
-            if !(0..10).contains(&xi) || !(0..10).contains(&yi) {
-                continue;
+        for delta in grid::ALL_AROUND {
+            if let Some(next) = input.offset(pos, delta) {
+                flashes += step(input, flashed, next);
             }
-
-            flashes += step(input, flashed, (xi, yi));
         }
     }
 
     flashes
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 11;
+
+    type Input<'a> = Grid<u32>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day11.txt"))),
-        1675
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        1656
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day11.txt"))),
-        515
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        195
     }
 }
@@ -1,12 +1,20 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use itertools::Itertools;
+use nom::{character::complete::alpha1, sequence::separated_pair};
 
-pub fn parse_input(input: &str) -> Vec<(&str, &str)> {
-    input
-        .lines()
-        .map(|line| line.split('-').collect_tuple().unwrap())
-        .collect()
+use crate::{
+    parse::{self, expect, lines, token, Expected},
+    ParseError, Solution,
+};
+
+pub fn parse_input(input: &str) -> Result<Vec<(&str, &str)>, ParseError> {
+    let cave = || expect(Expected::Description("a cave name"), alpha1);
+
+    parse::run(
+        Solver::DAY,
+        input,
+        lines(separated_pair(cave(), token("-"), cave())),
+    )
 }
 
 pub fn part_1(graph: &[(&str, &str)]) -> usize {
@@ -77,13 +85,95 @@ fn has_dupe(path: &[&str]) -> bool {
     false
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 12;
+
+    type Input<'a> = Vec<(&'a str, &'a str)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const SMALL: &str = "\
+start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end";
+
+#[cfg(test)]
+const MEDIUM: &str = "\
+dc-end
+HN-start
+start-kj
+dc-start
+dc-HN
+LN-dc
+HN-end
+kj-sa
+kj-HN
+kj-dc";
+
+#[cfg(test)]
+const LARGE: &str = "\
+fs-end
+he-DX
+fs-he
+start-DX
+pj-DX
+end-zg
+zg-sl
+zg-pj
+pj-he
+RW-he
+fs-DX
+pj-RW
+zg-RW
+start-pj
+he-WI
+zg-he
+pj-fs
+start-RW";
+
 crate::solutions! {
-    p1 => {
-        part_1(&parse_input(include_str!("../inputs/day12.txt"))),
-        4754
+    p1_small => {
+        part_1(&parse_input(SMALL).unwrap()),
+        10
+    },
+    p1_medium => {
+        part_1(&parse_input(MEDIUM).unwrap()),
+        19
+    },
+    p1_large => {
+        part_1(&parse_input(LARGE).unwrap()),
+        226
+    },
+    p2_small => {
+        part_2(&parse_input(SMALL).unwrap()),
+        36
+    },
+    p2_medium => {
+        part_2(&parse_input(MEDIUM).unwrap()),
+        103
     },
-    p2 => {
-        part_2(&parse_input(include_str!("../inputs/day12.txt"))),
-        143562
+    p2_large => {
+        part_2(&parse_input(LARGE).unwrap()),
+        3509
     }
 }
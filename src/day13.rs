@@ -1,65 +1,130 @@
 use std::collections::HashSet;
 
+use nom::{
+    branch::alt,
+    combinator::map,
+    sequence::{preceded, separated_pair},
+};
+
+use crate::{
+    parse::{self, blank_line, expect, lines, number, token, Expected, IResult},
+    ParseError, Solution,
+};
+
+pub type Dots = HashSet<(i32, i32)>;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Fold {
     X(i32),
     Y(i32),
 }
 
-pub fn parse_input(input: &str) -> (HashSet<(i32, i32)>, Vec<Fold>) {
-    let (dots, folds) = input.split_once("\n\n").unwrap();
-
-    let dots = dots
-        .lines()
-        .map(|l| {
-            l.split_once(',')
-                .map(|(a, b)| (a.parse().unwrap(), b.parse().unwrap()))
-                .unwrap()
-        })
-        .collect();
+pub fn parse_input(input: &str) -> Result<(Dots, Vec<Fold>), ParseError> {
+    let dot = separated_pair(number, token(","), number);
 
-    let folds = folds
-        .lines()
-        .map(|l| {
-            let n = l[13..].parse().unwrap();
-
-            if l.contains('x') {
-                Fold::X(n)
-            } else {
-                Fold::Y(n)
-            }
-        })
-        .collect();
+    parse::run(
+        Solver::DAY,
+        input,
+        separated_pair(
+            map(lines(dot), |dots| dots.into_iter().collect()),
+            blank_line,
+            lines(fold_instruction),
+        ),
+    )
+}
 
-    (dots, folds)
+fn fold_instruction(input: &str) -> IResult<'_, Fold> {
+    preceded(
+        token("fold along"),
+        expect(
+            Expected::OneOf(&["x=", "y="]),
+            alt((
+                map(preceded(token("x="), number), Fold::X),
+                map(preceded(token("y="), number), Fold::Y),
+            )),
+        ),
+    )(input)
 }
 
-pub fn part_1((mut dots, folds): (HashSet<(i32, i32)>, Vec<Fold>)) -> usize {
+pub fn part_1((dots, folds): &(Dots, Vec<Fold>)) -> usize {
+    let mut dots = dots.clone();
     fold(&mut dots, folds[0]);
     dots.len()
 }
 
-pub fn part_2((mut dots, folds): (HashSet<(i32, i32)>, Vec<Fold>)) {
-    for f in folds {
+pub fn part_2(input: &(Dots, Vec<Fold>)) -> String {
+    ocr(&fold_all(input))
+}
+
+/// Applies all the `folds` to the transparent paper.
+pub fn fold_all((dots, folds): &(Dots, Vec<Fold>)) -> Dots {
+    let mut dots = dots.clone();
+
+    for &f in folds {
         fold(&mut dots, f);
     }
 
-    let xmax = dots.iter().map(|(x, _)| *x).max().unwrap();
-    let ymax = dots.iter().map(|(_, y)| *y).max().unwrap();
-
-    for y in 0..=ymax {
-        for x in 0..=xmax {
-            if dots.contains(&(x, y)) {
-                print!("#");
-            } else {
-                print!(" ");
-            }
-        }
-        println!();
-    }
+    dots
+}
+
+/// Draws the dots as `#` characters, one line per row.
+pub fn render(dots: &Dots) -> String {
+    let xmax = dots.iter().map(|(x, _)| *x).max().unwrap_or(-1);
+    let ymax = dots.iter().map(|(_, y)| *y).max().unwrap_or(-1);
+
+    (0..=ymax)
+        .map(|y| {
+            (0..=xmax)
+                .map(|x| if dots.contains(&(x, y)) { '#' } else { ' ' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Letters of the Advent of Code font, 4 dots wide and 6 dots tall.
+const GLYPHS: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###.", ".#..", ".#..", ".#..", ".#..", "###."]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Reads the letters spelled by the dots, which are laid out every 5 columns. Letters that don't
+/// match any glyph come out as `?`.
+pub fn ocr(dots: &Dots) -> String {
+    let xmax = dots.iter().map(|(x, _)| *x).max().unwrap_or(-1);
+
+    (0..(xmax + 5) / 5)
+        .map(|i| {
+            GLYPHS
+                .iter()
+                .find(|(_, glyph)| {
+                    glyph.iter().zip(0..).all(|(row, y)| {
+                        row.chars()
+                            .zip(0..)
+                            .all(|(c, x)| (c == '#') == dots.contains(&(i * 5 + x, y)))
+                    })
+                })
+                .map_or('?', |&(letter, _)| letter)
+        })
+        .collect()
 }
 
-fn fold(dots: &mut HashSet<(i32, i32)>, fold: Fold) {
+fn fold(dots: &mut Dots, fold: Fold) {
     *dots = dots
         .drain()
         .map(|(x, y)| match fold {
@@ -69,13 +134,94 @@ fn fold(dots: &mut HashSet<(i32, i32)>, fold: Fold) {
         .collect();
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 13;
+
+    type Input<'a> = (Dots, Vec<Fold>);
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day13.txt"))),
-        706
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        17
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day13.txt"))),
-        ()
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        "?"
+    },
+    p2_render => {
+        render(&fold_all(&parse_input(EXAMPLE).unwrap())),
+        ["#####", "#   #", "#   #", "#   #", "#####"].join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_letters() {
+        let art = [
+            "#    ###  ####   ## ###    ## #### #  #",
+            "#    #  # #       # #  #    # #    #  #",
+            "#    #  # ###     # ###     # ###  ####",
+            "#    ###  #       # #  #    # #    #  #",
+            "#    # #  #    #  # #  # #  # #    #  #",
+            "#### #  # #     ##  ###   ##  #### #  #",
+        ];
+
+        let dots = art
+            .iter()
+            .zip(0..)
+            .flat_map(|(row, y)| {
+                row.chars()
+                    .zip(0..)
+                    .filter(|&(c, _)| c == '#')
+                    .map(move |(_, x)| (x, y))
+            })
+            .collect();
+
+        assert_eq!(ocr(&dots), "LRFJBJEH");
+        assert_eq!(render(&dots), art.join("\n"));
     }
 }
@@ -2,12 +2,31 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
+use nom::{
+    character::complete::{alpha1, satisfy},
+    combinator::map,
+    sequence::{pair, separated_pair},
+};
+
+use crate::{
+    parse::{self, blank_line, expect, lines, token, Expected, IResult},
+    ParseError, Solution,
+};
+
 pub type Pairs = HashMap<(u8, u8), u64>;
 pub type Rules = HashMap<(u8, u8), u8>;
 pub type Letters = HashMap<u8, u64>;
 
-pub fn parse_input(input: &str) -> (Pairs, Letters, Rules) {
-    let (template, rules) = input.split_once("\n\n").unwrap();
+pub fn parse_input(input: &str) -> Result<(Pairs, Letters, Rules), ParseError> {
+    let (template, rules) = parse::run(
+        Solver::DAY,
+        input,
+        separated_pair(
+            expect(Expected::Description("a polymer template"), alpha1),
+            blank_line,
+            lines(separated_pair(pair(element, element), token("->"), element)),
+        ),
+    )?;
 
     let pairs = template
         .bytes()
@@ -22,29 +41,30 @@ pub fn parse_input(input: &str) -> (Pairs, Letters, Rules) {
         map
     });
 
-    let rules = rules
-        .lines()
-        .map(|l| {
-            let (left, right) = l.split_once(" -> ").unwrap();
-            (
-                (left.as_bytes()[0], left.as_bytes()[1]),
-                right.as_bytes()[0],
-            )
-        })
-        .collect();
-
-    (pairs, letters, rules)
+    Ok((pairs, letters, rules.into_iter().collect()))
+}
+
+fn element(input: &str) -> IResult<'_, u8> {
+    map(
+        expect(
+            Expected::Description("an element"),
+            satisfy(|c| c.is_ascii_alphabetic()),
+        ),
+        |c| c as u8,
+    )(input)
 }
 
-pub fn part_1(input: (Pairs, Letters, Rules)) -> u64 {
+pub fn part_1(input: &(Pairs, Letters, Rules)) -> u64 {
     solve(input, 10)
 }
 
-pub fn part_2(input: (Pairs, Letters, Rules)) -> u64 {
+pub fn part_2(input: &(Pairs, Letters, Rules)) -> u64 {
     solve(input, 40)
 }
 
-pub fn solve((mut pairs, mut letters, rules): (Pairs, Letters, Rules), n: usize) -> u64 {
+pub fn solve((pairs, letters, rules): &(Pairs, Letters, Rules), n: usize) -> u64 {
+    let (mut pairs, mut letters) = (pairs.clone(), letters.clone());
+
     for _ in 0..n {
         let mut next = HashMap::new();
 
@@ -63,13 +83,56 @@ pub fn solve((mut pairs, mut letters, rules): (Pairs, Letters, Rules), n: usize)
     letters.values().max().unwrap() - letters.values().min().unwrap()
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 14;
+
+    type Input<'a> = (Pairs, Letters, Rules);
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day14.txt"))),
-        3143
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        1588
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day14.txt"))),
-        4110215602456
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        2188189693529
     }
 }
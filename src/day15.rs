@@ -1,19 +1,22 @@
-use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashMap},
-};
+use std::{cmp::Reverse, collections::BinaryHeap};
 
-pub type Grid = HashMap<(i32, i32), u32>;
+use crate::{
+    grid::Pos,
+    parse::{self, digit, grid, Expected, IResult},
+    Grid, ParseError, Solution,
+};
 
+/// An entry of the open set, ordered by its estimated total risk.
 #[derive(Debug, Clone)]
 pub struct Node {
-    pos: (i32, i32),
-    score: u32,
+    pos: Pos,
+    risk: u32,
+    estimate: u32,
 }
 
 impl PartialEq for Node {
     fn eq(&self, other: &Self) -> bool {
-        self.score == other.score
+        self.estimate == other.estimate
     }
 }
 
@@ -21,102 +24,287 @@ impl Eq for Node {}
 
 impl PartialOrd for Node {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.score.partial_cmp(&other.score)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Node {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.score.cmp(&other.score)
+        self.estimate.cmp(&other.estimate)
     }
 }
 
-pub fn parse_input(input: &str) -> Grid {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, l)| {
-            l.chars()
-                .enumerate()
-                .map(move |(x, n)| ((x as i32, y as i32), n.to_digit(10).unwrap()))
-        })
-        .collect()
+pub fn parse_input(input: &str) -> Result<Grid<u32>, ParseError> {
+    parse::run(Solver::DAY, input, grid(risk))
 }
 
-pub fn part_1(grid: Grid) -> u32 {
-    a_star(&grid, (0, 0), (99, 99))
+/// A risk level, which is never 0 so that every step through the cave costs something.
+fn risk(input: &str) -> IResult<'_, u32> {
+    match digit(input)? {
+        (_, 0) => Err(parse::fail(
+            input,
+            Expected::Description("a risk level between 1 and 9"),
+        )),
+        ok => Ok(ok),
+    }
 }
 
-pub fn part_2(mut grid: Grid) -> u32 {
-    for (x, y) in (0..100).flat_map(|y| (0..100).map(move |x| (x, y))) {
-        for (tx, ty) in (0..5).flat_map(|y| (0..5).map(move |x| (x, y))) {
-            let mut v = grid[&(x, y)] + (tx + ty) as u32;
-            if v > 9 {
-                v -= 9;
-            }
-            grid.insert((x + tx * 100, y + ty * 100), v);
-        }
+pub fn part_1(cave: &Grid<u32>) -> u32 {
+    lowest_risk(cave)
+}
+
+pub fn part_2(cave: &Grid<u32>) -> u32 {
+    lowest_risk(&tile(cave, 5))
+}
+
+/// Total risk of the safest path from the top left to the bottom right corner of the cave.
+pub fn lowest_risk(cave: &Grid<u32>) -> u32 {
+    safest_path(cave).risk
+}
+
+/// The safest path from the top left to the bottom right corner of the cave.
+///
+/// # Panics
+///
+/// Panics if the cave is empty.
+pub fn safest_path(cave: &Grid<u32>) -> Path {
+    assert!(!cave.is_empty(), "the cave is empty");
+    a_star(cave, (0, 0), (cave.width() - 1, cave.height() - 1))
+}
+
+/// A path through the cave, along with its total risk, which doesn't count the starting cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub risk: u32,
+    /// Positions along the path, from start to end.
+    pub cells: Vec<Pos>,
+}
+
+/// Draws the risk map with the cells along `path` replaced by `#`.
+pub fn render_path(cave: &Grid<u32>, path: &Path) -> String {
+    let mut map = cave.map(|&risk| char::from_digit(risk, 10).unwrap_or('?'));
+
+    for &pos in &path.cells {
+        map[pos] = '#';
     }
 
-    a_star(&grid, (0, 0), (499, 499))
+    map.to_string()
 }
 
-pub fn a_star(grid: &Grid, start: (i32, i32), end: (i32, i32)) -> u32 {
-    let mut open = BinaryHeap::new();
+/// Repeats the cave `factor` times in both directions. Every repetition to the right or down adds
+/// 1 to the risk levels, which wrap back to 1 after 9.
+///
+/// # Panics
+///
+/// Panics if `factor` is 0.
+pub fn tile(cave: &Grid<u32>, factor: usize) -> Grid<u32> {
+    assert!(factor > 0, "the cave must be tiled at least once");
+
+    let (w, h) = (cave.width(), cave.height());
+
+    Grid::from_fn(w * factor, h * factor, |(x, y)| {
+        (cave[(x % w, y % h)] + (x / w + y / h) as u32 + 8) % 9 + 1
+    })
+}
 
-    let mut g_scores: HashMap<_, _> = grid.keys().map(|&pos| (pos, std::u32::MAX)).collect();
-    let mut f_scores: HashMap<_, _> = g_scores.clone();
+/// Finds the safest path between two positions of the cave with A*.
+///
+/// # Panics
+///
+/// Panics if `start` or `end` is outside of the cave.
+pub fn a_star(cave: &Grid<u32>, start: Pos, end: Pos) -> Path {
+    assert!(cave.contains(start), "start {:?} out of bounds", start);
+    assert!(cave.contains(end), "end {:?} out of bounds", end);
 
-    *g_scores.entry(start).or_default() = 0;
-    *f_scores.entry(start).or_default() = estimate(grid, start, end);
+    // Parsed caves have no risk level of 0, but others may
+    let step = cave.cells().iter().copied().min().unwrap_or_default();
 
-    let deltas = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+    let mut risks = cave.map(|_| u32::MAX);
+    let mut came_from = cave.map(|_| None);
+    let mut open = BinaryHeap::new();
 
+    risks[start] = 0;
     open.push(Reverse(Node {
         pos: start,
-        score: 0,
+        risk: 0,
+        estimate: estimate(start, end, step),
     }));
 
-    while let Some(Reverse(Node { pos, score })) = open.pop() {
+    while let Some(Reverse(Node { pos, risk, .. })) = open.pop() {
         if pos == end {
-            return score;
-        }
+            let mut cells = vec![end];
+            while let Some(prev) = came_from[cells[cells.len() - 1]] {
+                cells.push(prev);
+            }
+            cells.reverse();
 
-        for (dx, dy) in deltas {
-            let new_pos = (pos.0 + dx, pos.1 + dy);
+            return Path { risk, cells };
+        }
 
-            if let Some(&cost) = grid.get(&new_pos) {
-                let tentative_score = g_scores[&pos] + cost;
+        // Rather than updating nodes in the heap, cheaper paths push new ones, and the stale ones
+        // are skipped here
+        if risk > risks[pos] {
+            continue;
+        }
 
-                if tentative_score < g_scores[&new_pos] {
-                    g_scores.insert(new_pos, tentative_score);
-                    f_scores.insert(new_pos, tentative_score + estimate(grid, new_pos, end));
+        for next in cave.neighbours4(pos) {
+            let risk = risk + cave[next];
 
-                    if !open.iter().any(|Reverse(n)| n.pos == new_pos) {
-                        open.push(Reverse(Node {
-                            pos: new_pos,
-                            score: score + cost,
-                        }));
-                    }
-                }
+            if risk < risks[next] {
+                risks[next] = risk;
+                came_from[next] = Some(pos);
+                open.push(Reverse(Node {
+                    pos: next,
+                    risk,
+                    estimate: risk + estimate(next, end, step),
+                }));
             }
         }
     }
 
-    unreachable!()
+    unreachable!("every cell of the cave is reachable from any other")
 }
 
-fn estimate(grid: &Grid, a: (i32, i32), b: (i32, i32)) -> u32 {
-    grid[&a] + ((b.0 - a.0).abs() + (b.1 - a.1).abs()) as u32
+/// Lower bound on the risk between two positions, since every step costs at least `step`. Never
+/// overestimating it keeps the first path found to the end the safest one.
+fn estimate(a: Pos, b: Pos, step: u32) -> u32 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u32 * step
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 15;
+
+    type Input<'a> = Grid<u32>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day15.txt"))),
-        626
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        40
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day15.txt"))),
-        2966
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        315
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_risk_levels_of_zero() {
+        assert_eq!(
+            parse_input("10\n09").unwrap_err().to_string(),
+            "day 15, line 1, column 2: expected a risk level between 1 and 9"
+        );
+    }
+
+    #[test]
+    fn tiles_caves() {
+        let cave = tile(&parse_input("8").unwrap(), 3);
+        assert_eq!(cave.to_string(), "891\n912\n123");
+
+        let cave = tile(&parse_input(EXAMPLE).unwrap(), 5);
+        assert_eq!(
+            cave.row(0).iter().map(u32::to_string).collect::<String>(),
+            "11637517422274862853338597396444961841755517295286"
+        );
+        assert_eq!(
+            cave.row(49).iter().map(u32::to_string).collect::<String>(),
+            "67554889357866599146897761125791887223681299833479"
+        );
+    }
+
+    #[test]
+    fn finds_paths() {
+        let cave = parse_input("1163\n1381").unwrap();
+        let path = safest_path(&cave);
+
+        assert_eq!(path.cells, [(0, 0), (1, 0), (2, 0), (3, 0), (3, 1)]);
+        assert_eq!(render_path(&cave, &path), "####\n138#");
+
+        for cave in [
+            parse_input(EXAMPLE).unwrap(),
+            tile(&parse_input(EXAMPLE).unwrap(), 5),
+        ] {
+            let path = safest_path(&cave);
+
+            assert_eq!(path.cells.first(), Some(&(0, 0)));
+            assert_eq!(
+                path.cells.last(),
+                Some(&(cave.width() - 1, cave.height() - 1))
+            );
+            assert!(path
+                .cells
+                .windows(2)
+                .all(|w| cave.neighbours4(w[0]).any(|pos| pos == w[1])));
+            assert_eq!(
+                path.cells[1..].iter().map(|&pos| cave[pos]).sum::<u32>(),
+                path.risk
+            );
+        }
+
+        assert_eq!(safest_path(&parse_input(EXAMPLE).unwrap()).risk, 40);
+    }
+
+    #[test]
+    fn finds_the_safest_path_through_free_cells() {
+        let cells = "999909019910091110091900"
+            .chars()
+            .map(|c| c.to_digit(10).unwrap())
+            .collect();
+        let cave = Grid::from_vec(cells, 4);
+
+        assert_eq!(lowest_risk(&cave), 10);
+        assert!(parse_input(&cave.to_string()).is_err());
+    }
+
+    #[test]
+    fn solves_caves_of_any_size() {
+        assert_eq!(lowest_risk(&parse_input("1163\n1381").unwrap()), 11);
+        assert_eq!(lowest_risk(&parse_input("1\n2\n3").unwrap()), 5);
+        assert_eq!(lowest_risk(&tile(&parse_input("12\n34").unwrap(), 3)), 44);
+    }
+
+    #[test]
+    #[should_panic(expected = "tiled at least once")]
+    fn rejects_empty_tilings() {
+        tile(&parse_input(EXAMPLE).unwrap(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn rejects_paths_leaving_the_cave() {
+        a_star(&parse_input("1163\n1381").unwrap(), (0, 0), (4, 1));
     }
 }
@@ -1,37 +1,239 @@
-use std::collections::VecDeque;
+use std::{fmt, ops::Range};
 
+use nom::multi::many1;
+
+use crate::{
+    parse::{self, hex_digit, Expected},
+    ParseError, Solution,
+};
+
+/// How many operators a packet can be nested in, whether it's decoded or built with
+/// [`Packet::operator`]. This also bounds the recursion of everything walking packets, from
+/// evaluation to rendering.
+pub const MAX_DEPTH: usize = 256;
+
+/// Why a transmission can't be decoded or evaluated, or a packet can't be built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The transmission ended `needed` bits into a field, with only `remaining` bits left.
+    Truncated { needed: usize, remaining: usize },
+    /// The version doesn't fit in 3 bits.
+    Version(u32),
+    /// The type ID is neither a literal nor one of the operators.
+    UnknownType(u32),
+    /// An operator has a number of sub-packets its type doesn't allow.
+    Operands { ptype: u32, count: usize },
+    /// The sub-packets of an operator took `actual` bits rather than the `expected` ones.
+    LengthMismatch { expected: usize, actual: usize },
+    /// A packet is nested in more than [`MAX_DEPTH`] operators.
+    TooDeep,
+    /// A literal or the value of an operator doesn't fit in 64 bits.
+    Overflow,
+}
+
+impl Error {
+    /// What the decoder expected instead, for reporting as a [`ParseError`].
+    fn expected(&self) -> Expected {
+        Expected::Description(match self {
+            Error::Truncated { .. } => "more bits",
+            Error::Version(_) => "a version below 8",
+            Error::UnknownType(_) => "a known type ID",
+            Error::Operands { ptype: 5..=7, .. } => "two sub-packets to compare",
+            Error::Operands { .. } => "at least one sub-packet",
+            Error::LengthMismatch { .. } => "sub-packets as long as announced",
+            Error::TooDeep => "fewer nested packets",
+            Error::Overflow => "a literal that fits in 64 bits",
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated { needed, remaining } => write!(
+                f,
+                "transmission truncated: needed {} bits, {} left",
+                needed, remaining
+            ),
+            Error::Version(version) => write!(f, "version {} doesn't fit in 3 bits", version),
+            Error::UnknownType(ptype) => write!(f, "unknown packet type {}", ptype),
+            Error::Operands { ptype, count } => {
+                write!(f, "packet type {} can't have {} sub-packets", ptype, count)
+            }
+            Error::LengthMismatch { expected, actual } => write!(
+                f,
+                "sub-packets took {} bits instead of {}",
+                actual, expected
+            ),
+            Error::TooDeep => write!(f, "packets nested more than {} deep", MAX_DEPTH),
+            Error::Overflow => write!(f, "value doesn't fit in 64 bits"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Reads bits most significant first out of a packed buffer.
 #[derive(Debug, Clone)]
 pub struct BitStream {
-    rest: VecDeque<u8>,
-    current: u8,
-    remaining_in_current: usize,
+    bytes: Vec<u8>,
+    /// Number of bits in the stream, which may stop short of the end of the last byte.
+    len: usize,
+    pos: usize,
 }
 
 impl BitStream {
-    pub fn pop(&mut self, mut n: usize) -> u32 {
-        let mut res = 0;
+    /// A stream over all the bits of `bytes`.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        let bytes = bytes.into();
+
+        Self {
+            len: bytes.len() * 8,
+            bytes,
+            pos: 0,
+        }
+    }
 
+    /// A stream over the bits of a hex transmission, or `None` if it isn't all hex digits.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        hex.chars()
+            .map(|c| c.to_digit(16))
+            .collect::<Option<_>>()
+            .map(Self::from_digits)
+    }
+
+    fn from_digits(digits: Vec<u32>) -> Self {
+        Self {
+            len: digits.len() * 4,
+            bytes: digits
+                .chunks(2)
+                .map(|pair| (pair[0] << 4 | pair.get(1).unwrap_or(&0)) as u8)
+                .collect(),
+            pos: 0,
+        }
+    }
+
+    /// Reads the next `n` bits, up to 64.
+    pub fn pop(&mut self, n: usize) -> Result<u64, Error> {
+        assert!(n <= 64, "can't read {} bits at once", n);
+
+        if n > self.remaining() {
+            return Err(Error::Truncated {
+                needed: n,
+                remaining: self.remaining(),
+            });
+        }
+
+        let mut res = 0u64;
+        let mut n = n;
+
+        // Take as many bits as wanted from each byte at once
         while n > 0 {
-            if self.remaining_in_current > 0 {
-                res = (res << 1) | ((self.current >> 3) & 0x1) as u32;
-                self.current <<= 1;
-                self.remaining_in_current -= 1;
-                n -= 1;
-            } else {
-                self.current = self.rest.pop_front().expect("out of bits");
-                self.remaining_in_current = 4;
-            }
+            let offset = self.pos % 8;
+            let take = (8 - offset).min(n);
+            let bits = (self.bytes[self.pos / 8] as u64 >> (8 - offset - take)) & ((1 << take) - 1);
+
+            res = (res << take) | bits;
+            self.pos += take;
+            n -= take;
         }
 
-        res
+        Ok(res)
     }
 
     pub fn remaining(&self) -> usize {
-        self.rest.len() * 4 + self.remaining_in_current
+        self.len - self.pos
+    }
+
+    /// Number of bits read so far.
+    pub fn position(&self) -> usize {
+        self.pos
     }
 }
 
-#[derive(Debug, Clone)]
+/// Packs bits most significant first, for encoding packets back into a transmission.
+#[derive(Debug, Clone, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the `n` lowest bits of `value`, up to 64.
+    pub fn push(&mut self, value: u64, n: usize) {
+        assert!(n <= 64, "can't write {} bits at once", n);
+
+        let mut n = n;
+
+        // Fill the last byte as much as possible at each step
+        while n > 0 {
+            let offset = self.len % 8;
+            if offset == 0 {
+                self.bytes.push(0);
+            }
+
+            let take = (8 - offset).min(n);
+            let bits = (value >> (n - take)) & ((1 << take) - 1);
+
+            *self.bytes.last_mut().unwrap() |= (bits << (8 - offset - take)) as u8;
+            self.len += take;
+            n -= take;
+        }
+    }
+
+    /// Appends all the bits written to `other`.
+    pub fn append(&mut self, other: &BitWriter) {
+        for (i, &byte) in other.bytes.iter().enumerate() {
+            let n = (other.len - i * 8).min(8);
+            self.push((byte >> (8 - n)) as u64, n);
+        }
+    }
+
+    /// Number of bits written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The bits written so far, padded with zeros to a whole number of bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The bits written so far as hex digits, padded with zeros to a whole number of bytes.
+    pub fn to_hex(&self) -> String {
+        self.bytes.iter().map(|b| format!("{:02X}", b)).collect()
+    }
+}
+
+/// How an operator packet tells the size of its sub-packets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthType {
+    /// Total length of the sub-packets in bits, on 15 bits (length type ID 0).
+    Bits,
+    /// Number of sub-packets, on 11 bits (length type ID 1).
+    Count,
+}
+
+/// Where a packet sits in its transmission, as found by [`Packet::parse_layout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    /// Offsets of the first bit of the packet and of the one right after it.
+    pub bits: Range<usize>,
+    /// How many operators the packet is nested in.
+    pub depth: usize,
+    /// How the size of the sub-packets was given, for operators.
+    pub length_type: Option<LengthType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Packet {
     version: u32,
     ptype: u32,
@@ -39,18 +241,102 @@ pub struct Packet {
 }
 
 impl Packet {
-    pub fn parse(stream: &mut BitStream) -> Packet {
-        let version = stream.pop(3);
-        let ptype = stream.pop(3);
-        let payload = Payload::parse(stream, ptype);
+    /// A literal packet of value `n`.
+    pub fn literal(version: u32, n: u64) -> Result<Packet, Error> {
+        check_version(version)?;
 
-        Packet {
+        Ok(Packet {
+            version,
+            ptype: 4,
+            payload: Payload::Literal(n),
+        })
+    }
+
+    /// An operator of type `ptype` applied to `pkts`, which must be as many as the operator takes
+    /// and leave it nested no deeper than [`MAX_DEPTH`].
+    pub fn operator(version: u32, ptype: u32, pkts: Vec<Packet>) -> Result<Packet, Error> {
+        check_version(version)?;
+        check_operands(ptype, pkts.len())?;
+
+        if pkts.iter().any(|pkt| pkt.height() >= MAX_DEPTH) {
+            return Err(Error::TooDeep);
+        }
+
+        Ok(Packet {
             version,
             ptype,
-            payload,
+            payload: Payload::Operator(pkts),
+        })
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn ptype(&self) -> u32 {
+        self.ptype
+    }
+
+    pub fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    /// How many operators are nested in this packet, along its deepest branch.
+    fn height(&self) -> usize {
+        match self.payload {
+            Payload::Literal(_) => 0,
+            Payload::Operator(ref pkts) => 1 + pkts.iter().map(Packet::height).max().unwrap_or(0),
         }
     }
 
+    pub fn parse(stream: &mut BitStream) -> Result<Packet, Error> {
+        Self::decode(stream, 0, &mut Vec::new())
+    }
+
+    /// Decodes a packet along with where each of its packets sits in the transmission, in the
+    /// order they appear in it.
+    pub fn parse_layout(stream: &mut BitStream) -> Result<(Packet, Vec<Layout>), Error> {
+        let mut layout = Vec::new();
+        let packet = Self::decode(stream, 0, &mut layout)?;
+
+        Ok((packet, layout))
+    }
+
+    fn decode(
+        stream: &mut BitStream,
+        depth: usize,
+        layout: &mut Vec<Layout>,
+    ) -> Result<Packet, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::TooDeep);
+        }
+
+        let start = stream.position();
+        let version = stream.pop(3)? as u32;
+        let ptype = stream.pop(3)? as u32;
+
+        let index = layout.len();
+        layout.push(Layout {
+            bits: start..start,
+            depth,
+            length_type: None,
+        });
+
+        let payload = Payload::parse(stream, ptype, depth, layout)?;
+
+        if let Payload::Operator(ref pkts) = payload {
+            check_operands(ptype, pkts.len())?;
+        }
+
+        layout[index].bits.end = stream.position();
+
+        Ok(Packet {
+            version,
+            ptype,
+            payload,
+        })
+    }
+
     pub fn cumulative_version(&self) -> u32 {
         match self.payload {
             Payload::Literal(_) => self.version,
@@ -60,100 +346,768 @@ impl Packet {
         }
     }
 
-    pub fn value(&self) -> u64 {
+    pub fn value(&self) -> Result<u64, Error> {
+        let pkts = match self.payload {
+            Payload::Literal(n) => return Ok(n),
+            Payload::Operator(ref pkts) => pkts,
+        };
+
+        let values = pkts
+            .iter()
+            .map(Packet::value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let value = match (self.ptype, &values[..]) {
+            (0, [_, ..]) => values.iter().try_fold(0u64, |acc, &v| acc.checked_add(v)),
+            (1, [_, ..]) => values.iter().try_fold(1u64, |acc, &v| acc.checked_mul(v)),
+            (2, [_, ..]) => values.iter().copied().min(),
+            (3, [_, ..]) => values.iter().copied().max(),
+            (5, &[a, b]) => Some((a > b) as u64),
+            (6, &[a, b]) => Some((a < b) as u64),
+            (7, &[a, b]) => Some((a == b) as u64),
+            (ptype @ (0..=3 | 5..=7), _) => {
+                return Err(Error::Operands {
+                    ptype,
+                    count: values.len(),
+                })
+            }
+            (ptype, _) => return Err(Error::UnknownType(ptype)),
+        };
+
+        value.ok_or(Error::Overflow)
+    }
+
+    /// Encodes the packet as a hex transmission, see [`Packet::encode`].
+    pub fn to_hex(&self, length_type: LengthType) -> String {
+        let mut writer = BitWriter::new();
+        self.encode(&mut writer, length_type);
+        writer.to_hex()
+    }
+
+    /// Writes the packet to `writer`. Operators record the size of their sub-packets with
+    /// `length_type` when it fits, and with the other one otherwise. Literals are written in as few
+    /// groups of 4 bits as they fit in.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the version or type ID don't fit in 3 bits, or if the sub-packets of an operator
+    /// don't fit either length type.
+    pub fn encode(&self, writer: &mut BitWriter, length_type: LengthType) {
+        assert!(self.version < 8, "version {} is too large", self.version);
+        assert!(self.ptype < 8, "type ID {} is too large", self.ptype);
+
+        writer.push(self.version as u64, 3);
+        writer.push(self.ptype as u64, 3);
+
         match self.payload {
-            Payload::Literal(n) => n,
-            Payload::Operator(ref pkts) => match self.ptype {
-                0 => pkts.iter().map(Packet::value).sum(),
-                1 => pkts.iter().map(Packet::value).product(),
-                2 => pkts.iter().map(Packet::value).min().unwrap(),
-                3 => pkts.iter().map(Packet::value).max().unwrap(),
-                5 => (pkts[0].value() > pkts[1].value()) as u64,
-                6 => (pkts[0].value() < pkts[1].value()) as u64,
-                7 => (pkts[0].value() == pkts[1].value()) as u64,
-                _ => unreachable!("invalid packet type"),
-            },
+            Payload::Literal(n) => {
+                let groups = (64 - n.leading_zeros() as usize).div_ceil(4).max(1);
+
+                for i in (0..groups).rev() {
+                    writer.push((i > 0) as u64, 1);
+                    writer.push(n >> (i * 4), 4);
+                }
+            }
+            Payload::Operator(ref pkts) => {
+                let mut sub = BitWriter::new();
+                for pkt in pkts {
+                    pkt.encode(&mut sub, length_type);
+                }
+
+                let fits_bits = sub.len() < 1 << 15;
+                let fits_count = pkts.len() < 1 << 11;
+
+                let length_type = match length_type {
+                    LengthType::Bits if fits_bits => LengthType::Bits,
+                    LengthType::Count if fits_count => LengthType::Count,
+                    _ if fits_bits => LengthType::Bits,
+                    _ if fits_count => LengthType::Count,
+                    _ => panic!("too many sub-packets to encode"),
+                };
+
+                match length_type {
+                    LengthType::Bits => {
+                        writer.push(0, 1);
+                        writer.push(sub.len() as u64, 15);
+                    }
+                    LengthType::Count => {
+                        writer.push(1, 1);
+                        writer.push(pkts.len() as u64, 11);
+                    }
+                }
+
+                writer.append(&sub);
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Name of the operator with type ID `ptype`.
+fn operator_name(ptype: u32) -> Option<&'static str> {
+    Some(match ptype {
+        0 => "sum",
+        1 => "product",
+        2 => "min",
+        3 => "max",
+        5 => "gt",
+        6 => "lt",
+        7 => "eq",
+        _ => return None,
+    })
+}
+
+/// Renders the packet as an expression such as `v3:sum(v6:3, v1:product(v2:2, v5:7))`, where
+/// every packet is prefixed with its version. The alternate form (`{:#}`) leaves the versions out.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !f.alternate() {
+            write!(f, "v{}:", self.version)?;
+        }
+
+        let pkts = match self.payload {
+            Payload::Literal(n) => return write!(f, "{}", n),
+            Payload::Operator(ref pkts) => pkts,
+        };
+
+        match operator_name(self.ptype) {
+            Some(name) => write!(f, "{}(", name)?,
+            None => write!(f, "type{}(", self.ptype)?,
+        }
+        for (i, pkt) in pkts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            pkt.fmt(f)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Lists the packets of a transmission one per line, indented by depth, along with the range of
+/// bits each one takes. `layout` is the one decoded along with `packet`.
+pub fn dump(packet: &Packet, layout: &[Layout]) -> String {
+    fn walk<'a>(
+        packet: &Packet,
+        layout: &mut impl Iterator<Item = &'a Layout>,
+        width: usize,
+        lines: &mut Vec<String>,
+    ) {
+        let Some(entry) = layout.next() else {
+            return;
+        };
+        let bits = &entry.bits;
+
+        let what = match packet.payload {
+            Payload::Literal(n) => format!("literal {}", n),
+            Payload::Operator(ref pkts) => {
+                let name = operator_name(packet.ptype).unwrap_or("operator");
+
+                match entry.length_type {
+                    Some(LengthType::Count) => format!("{}, {} sub-packets", name, pkts.len()),
+                    // Past the header, the length type ID and the 15 bits of length
+                    _ => format!("{}, {} bits of sub-packets", name, bits.len() - 22),
+                }
+            }
+        };
+
+        lines.push(format!(
+            "{:>w$}..{:<w$} {:indent$}v{} {}",
+            bits.start,
+            bits.end,
+            "",
+            packet.version,
+            what,
+            w = width,
+            indent = entry.depth * 2,
+        ));
+
+        if let Payload::Operator(ref pkts) = packet.payload {
+            for pkt in pkts {
+                walk(pkt, layout, width, lines);
+            }
+        }
+    }
+
+    let width = layout.first().map_or(1, |l| l.bits.end.to_string().len());
+    let mut lines = Vec::new();
+    walk(packet, &mut layout.iter(), width, &mut lines);
+
+    lines.join("\n")
+}
+
+/// Checks that `version` fits in the 3 bits of a packet header.
+fn check_version(version: u32) -> Result<(), Error> {
+    match version {
+        0..=7 => Ok(()),
+        _ => Err(Error::Version(version)),
+    }
+}
+
+/// Checks that an operator of type `ptype` can have `count` sub-packets.
+fn check_operands(ptype: u32, count: usize) -> Result<(), Error> {
+    match (ptype, count) {
+        (0..=3, 1..) | (5..=7, 2) => Ok(()),
+        (0..=3 | 5..=7, _) => Err(Error::Operands { ptype, count }),
+        _ => Err(Error::UnknownType(ptype)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Payload {
     Literal(u64),
     Operator(Vec<Packet>),
 }
 
 impl Payload {
-    pub fn parse(stream: &mut BitStream, ptype: u32) -> Self {
+    fn parse(
+        stream: &mut BitStream,
+        ptype: u32,
+        depth: usize,
+        layout: &mut Vec<Layout>,
+    ) -> Result<Self, Error> {
         match ptype {
             4 => Self::parse_literal(stream),
-            _ => Self::parse_operator(stream),
+            _ => Self::parse_operator(stream, depth, layout),
         }
     }
 
-    fn parse_literal(stream: &mut BitStream) -> Self {
-        let mut lit = 0;
+    fn parse_literal(stream: &mut BitStream) -> Result<Self, Error> {
+        let mut lit = 0u64;
 
         loop {
-            let digit = stream.pop(5);
+            let digit = stream.pop(5)?;
 
-            lit = (lit << 4) | (digit & 0xf) as u64;
+            if lit.leading_zeros() < 4 {
+                return Err(Error::Overflow);
+            }
+            lit = (lit << 4) | (digit & 0xf);
 
             if (digit & 0x10) == 0 {
                 break;
             }
         }
 
-        Self::Literal(lit)
+        Ok(Self::Literal(lit))
     }
 
-    fn parse_operator(stream: &mut BitStream) -> Self {
-        let length_id = stream.pop(1);
+    fn parse_operator(
+        stream: &mut BitStream,
+        depth: usize,
+        layout: &mut Vec<Layout>,
+    ) -> Result<Self, Error> {
+        let length_id = stream.pop(1)?;
+
+        if let Some(packet) = layout.last_mut() {
+            packet.length_type = Some(match length_id {
+                0 => LengthType::Bits,
+                _ => LengthType::Count,
+            });
+        }
 
         let pkts = if length_id == 0 {
-            let to_be_read = stream.pop(15) as usize;
-            let remaining_at_end = stream.remaining() - to_be_read;
+            let to_be_read = stream.pop(15)? as usize;
+            let remaining_at_end =
+                stream
+                    .remaining()
+                    .checked_sub(to_be_read)
+                    .ok_or(Error::Truncated {
+                        needed: to_be_read,
+                        remaining: stream.remaining(),
+                    })?;
 
+            let start = stream.position();
             let mut pkts = Vec::new();
-            while stream.remaining() != remaining_at_end {
-                pkts.push(Packet::parse(stream));
+            while stream.remaining() > remaining_at_end {
+                pkts.push(Packet::decode(stream, depth + 1, layout)?);
+            }
+
+            // The last sub-packet may have run past the announced length
+            if stream.remaining() != remaining_at_end {
+                return Err(Error::LengthMismatch {
+                    expected: to_be_read,
+                    actual: stream.position() - start,
+                });
             }
 
             pkts
         } else {
-            (0..stream.pop(11)).map(|_| Packet::parse(stream)).collect()
+            (0..stream.pop(11)?)
+                .map(|_| Packet::decode(stream, depth + 1, layout))
+                .collect::<Result<_, _>>()?
         };
 
-        Self::Operator(pkts)
+        Ok(Self::Operator(pkts))
     }
 }
 
-pub fn parse_input(input: &str) -> BitStream {
-    BitStream {
-        rest: input
-            .chars()
-            .map(|c| c.to_digit(16).unwrap() as u8)
-            .collect(),
-        current: 0,
-        remaining_in_current: 0,
-    }
+/// The outermost packet of a transmission, along with the value of its expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transmission {
+    pub packet: Packet,
+    pub value: u64,
+}
+
+/// Decodes the outermost packet of the transmission and evaluates it. Decoding errors point at the
+/// hex digit the decoder gave up on, and values that don't fit in 64 bits at the whole
+/// transmission.
+pub fn parse_input(input: &str) -> Result<Transmission, ParseError> {
+    let digits = parse::run(Solver::DAY, input, many1(hex_digit))?;
+    let mut stream = BitStream::from_digits(digits);
+
+    let error = |column, expected| ParseError {
+        day: Solver::DAY,
+        line: 1,
+        column,
+        expected,
+    };
+
+    let packet =
+        Packet::parse(&mut stream).map_err(|e| error(stream.position() / 4 + 1, e.expected()))?;
+
+    // Decoding already checked the operands, so evaluating can only overflow
+    let value = packet.value().map_err(|_| {
+        error(
+            1,
+            Expected::Description("an expression whose value fits in 64 bits"),
+        )
+    })?;
+
+    Ok(Transmission { packet, value })
 }
 
-pub fn part_1(mut stream: BitStream) -> u32 {
-    Packet::parse(&mut stream).cumulative_version()
+pub fn part_1(transmission: &Transmission) -> u32 {
+    transmission.packet.cumulative_version()
 }
 
-pub fn part_2(mut stream: BitStream) -> u64 {
-    Packet::parse(&mut stream).value()
+pub fn part_2(transmission: &Transmission) -> u64 {
+    transmission.value
+}
+
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 16;
+
+    type Input<'a> = Transmission;
+    type Part1 = u32;
+    type Part2 = u64;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
 }
 
 crate::solutions! {
-    p1 => {
-        part_1(parse_input(include_str!("../inputs/day16.txt"))),
-        852
+    p1_nested => {
+        part_1(&parse_input("8A004A801A8002F478").unwrap()),
+        16
+    },
+    p1_two_operators => {
+        part_1(&parse_input("620080001611562C8802118E34").unwrap()),
+        12
+    },
+    p1_two_operators_by_length => {
+        part_1(&parse_input("C0015000016115A2E0802F182340").unwrap()),
+        23
+    },
+    p1_five_literals => {
+        part_1(&parse_input("A0016C880162017C3686B18A3D4780").unwrap()),
+        31
     },
-    p2 => {
-        part_2(parse_input(include_str!("../inputs/day16.txt"))),
-        19348959966392
+    p2_sum => {
+        part_2(&parse_input("C200B40A82").unwrap()),
+        3
+    },
+    p2_product => {
+        part_2(&parse_input("04005AC33890").unwrap()),
+        54
+    },
+    p2_minimum => {
+        part_2(&parse_input("880086C3E88112").unwrap()),
+        7
+    },
+    p2_maximum => {
+        part_2(&parse_input("CE00C43D881120").unwrap()),
+        9
+    },
+    p2_less_than => {
+        part_2(&parse_input("D8005AC2A8F0").unwrap()),
+        1
+    },
+    p2_greater_than => {
+        part_2(&parse_input("F600BC2D8F").unwrap()),
+        0
+    },
+    p2_equal_to => {
+        part_2(&parse_input("9C005AC2F8F0").unwrap()),
+        0
+    },
+    p2_nested_equal_to => {
+        part_2(&parse_input("9C0141080250320F1802104A08").unwrap()),
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn packet(hex: &str) -> Packet {
+        parse_input(hex).unwrap().packet
+    }
+
+    fn literal(version: u32, n: u64) -> Packet {
+        Packet {
+            version,
+            ptype: 4,
+            payload: Payload::Literal(n),
+        }
+    }
+
+    #[test]
+    fn literal_packet() {
+        assert_eq!(packet("D2FE28"), literal(6, 2021));
+    }
+
+    #[test]
+    fn operator_packets() {
+        assert_eq!(
+            packet("38006F45291200"),
+            Packet {
+                version: 1,
+                ptype: 6,
+                payload: Payload::Operator(vec![literal(6, 10), literal(2, 20)]),
+            }
+        );
+        assert_eq!(
+            packet("EE00D40C823060"),
+            Packet {
+                version: 7,
+                ptype: 3,
+                payload: Payload::Operator(vec![literal(2, 1), literal(4, 2), literal(1, 3)]),
+            }
+        );
+    }
+
+    fn decode(hex: &str) -> Result<Packet, Error> {
+        Packet::parse(&mut BitStream::from_hex(hex).unwrap())
+    }
+
+    fn operator(ptype: u32, pkts: Vec<Packet>) -> Packet {
+        Packet {
+            version: 0,
+            ptype,
+            payload: Payload::Operator(pkts),
+        }
+    }
+
+    #[test]
+    fn reads_bits() {
+        let mut stream = BitStream::from_bytes([0xD2, 0xFE, 0x28]);
+        assert_eq!(stream.pop(3), Ok(6));
+        assert_eq!(stream.pop(3), Ok(4));
+        assert_eq!(stream.pop(15), Ok(0b10111_11110_00101));
+        assert_eq!((stream.position(), stream.remaining()), (21, 3));
+
+        let mut stream = BitStream::from_hex("ABC").unwrap();
+        assert_eq!(stream.pop(12), Ok(0xABC));
+        assert_eq!(
+            stream.pop(1),
+            Err(Error::Truncated {
+                needed: 1,
+                remaining: 0
+            })
+        );
+
+        let mut stream = BitStream::from_hex("F0123456789ABCDEF0").unwrap();
+        assert_eq!(stream.pop(4), Ok(0xF));
+        assert_eq!(stream.pop(64), Ok(0x0123456789ABCDEF));
+        assert_eq!(stream.pop(0), Ok(0));
+        assert_eq!(stream.remaining(), 4);
+
+        assert!(BitStream::from_hex("D2FG").is_none());
+    }
+
+    #[test]
+    fn writes_bits() {
+        let mut writer = BitWriter::new();
+        writer.push(0b101, 3);
+        writer.push(u64::MAX, 64);
+        assert_eq!(writer.len(), 67);
+        assert_eq!(writer.to_hex(), "BFFFFFFFFFFFFFFFE0");
+
+        let mut other = BitWriter::new();
+        other.push(0, 1);
+        other.append(&writer);
+        assert_eq!(other.len(), 68);
+        assert_eq!(
+            other.as_bytes(),
+            [0x5F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xF0]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_transmissions() {
+        assert_eq!(
+            decode("D2FE"),
+            Err(Error::Truncated {
+                needed: 5,
+                remaining: 0
+            })
+        );
+        assert_eq!(
+            parse_input("D2FE").unwrap_err().to_string(),
+            "day 16, line 1, column 5: expected more bits"
+        );
+
+        let mut writer = BitWriter::new();
+        for (value, n) in [(0, 3), (0, 3), (0, 1), (10, 15)] {
+            writer.push(value, n);
+        }
+        literal(0, 1).encode(&mut writer, LengthType::Bits);
+        assert_eq!(
+            decode(&writer.to_hex()),
+            Err(Error::LengthMismatch {
+                expected: 10,
+                actual: 11
+            })
+        );
+
+        let lone_comparison = operator(5, vec![literal(0, 1)]);
+        assert_eq!(
+            decode(&lone_comparison.to_hex(LengthType::Bits)),
+            Err(Error::Operands { ptype: 5, count: 1 })
+        );
+        assert_eq!(
+            decode(&operator(0, vec![]).to_hex(LengthType::Count)),
+            Err(Error::Operands { ptype: 0, count: 0 })
+        );
+
+        assert_eq!(
+            decode(&literal(0, u64::MAX).to_hex(LengthType::Bits)),
+            Ok(literal(0, u64::MAX))
+        );
+        let mut writer = BitWriter::new();
+        writer.push(4, 6);
+        for _ in 0..17 {
+            writer.push(0x1f, 5);
+        }
+        writer.push(0, 5);
+        assert_eq!(decode(&writer.to_hex()), Err(Error::Overflow));
+    }
+
+    /// A literal nested in `depth` sums of one sub-packet each.
+    fn nested(depth: usize) -> String {
+        let mut writer = BitWriter::new();
+        for _ in 0..depth {
+            for (value, n) in [(0, 3), (0, 3), (1, 1), (1, 11)] {
+                writer.push(value, n);
+            }
+        }
+        literal(0, 7).encode(&mut writer, LengthType::Count);
+
+        writer.to_hex()
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        assert_eq!(decode(&nested(200_000)), Err(Error::TooDeep));
+        assert_eq!(
+            parse_input(&nested(MAX_DEPTH + 1)).unwrap_err().to_string(),
+            "day 16, line 1, column 1157: expected fewer nested packets"
+        );
+
+        // Everything walking the packets recurses as deep as the decoder
+        let deepest = packet(&nested(MAX_DEPTH));
+        assert_eq!(deepest.value(), Ok(7));
+        assert_eq!(deepest.cumulative_version(), 0);
+        assert!(format!("{:#}", deepest).starts_with("sum(sum("));
+
+        let mut stream = BitStream::from_hex(&nested(MAX_DEPTH)).unwrap();
+        let (deepest, layout) = Packet::parse_layout(&mut stream).unwrap();
+        assert_eq!(dump(&deepest, &layout).lines().count(), MAX_DEPTH + 1);
+    }
+
+    #[test]
+    fn rejects_overflowing_expressions() {
+        let product = operator(1, vec![literal(0, 1 << 63), literal(0, 4)]);
+
+        assert_eq!(
+            parse_input(&product.to_hex(LengthType::Bits))
+                .unwrap_err()
+                .to_string(),
+            "day 16, line 1, column 1: expected an expression whose value fits in 64 bits"
+        );
+    }
+
+    #[test]
+    fn builds_packets() {
+        let lt = Packet::operator(
+            1,
+            6,
+            vec![
+                Packet::literal(6, 10).unwrap(),
+                Packet::literal(2, 20).unwrap(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(lt, packet("38006F45291200"));
+        assert_eq!((lt.version(), lt.ptype()), (1, 6));
+        assert!(matches!(lt.payload(), Payload::Operator(pkts) if pkts.len() == 2));
+        assert_eq!(lt.to_hex(LengthType::Bits), "38006F45291200");
+
+        assert_eq!(Packet::literal(8, 0), Err(Error::Version(8)));
+        assert_eq!(
+            Packet::operator(0, 4, vec![lt.clone()]),
+            Err(Error::UnknownType(4))
+        );
+        assert_eq!(
+            Packet::operator(0, 7, vec![lt.clone()]),
+            Err(Error::Operands { ptype: 7, count: 1 })
+        );
+
+        let mut deepest = Packet::literal(0, 7).unwrap();
+        for _ in 0..MAX_DEPTH {
+            deepest = Packet::operator(0, 0, vec![deepest]).unwrap();
+        }
+        assert_eq!(deepest, packet(&nested(MAX_DEPTH)));
+        assert_eq!(Packet::operator(0, 0, vec![deepest]), Err(Error::TooDeep));
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert_eq!(
+            operator(6, vec![literal(0, 1); 3]).value(),
+            Err(Error::Operands { ptype: 6, count: 3 })
+        );
+        assert_eq!(
+            operator(4, vec![literal(0, 1)]).value(),
+            Err(Error::UnknownType(4))
+        );
+        assert_eq!(
+            operator(1, vec![literal(0, u64::MAX), literal(0, 2)]).value(),
+            Err(Error::Overflow)
+        );
+        assert_eq!(
+            operator(0, vec![operator(7, vec![literal(0, 1); 2]); 2]).value(),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn renders_expressions() {
+        assert_eq!(packet("D2FE28").to_string(), "v6:2021");
+        assert_eq!(packet("38006F45291200").to_string(), "v1:lt(v6:10, v2:20)");
+        assert_eq!(
+            format!("{:#}", packet("9C0141080250320F1802104A08")),
+            "eq(sum(1, 3), product(2, 2))"
+        );
+        assert_eq!(
+            format!("{:#}", operator(4, vec![literal(0, 1)])),
+            "type4(1)"
+        );
+    }
+
+    #[test]
+    fn dumps_layout() {
+        let layout = |hex| {
+            let (packet, layout) =
+                Packet::parse_layout(&mut BitStream::from_hex(hex).unwrap()).unwrap();
+            dump(&packet, &layout)
+        };
+
+        assert_eq!(
+            layout("38006F45291200"),
+            " 0..49 v1 lt, 27 bits of sub-packets\n\
+             22..33   v6 literal 10\n\
+             33..49   v2 literal 20"
+        );
+        assert_eq!(
+            layout("EE00D40C823060"),
+            " 0..51 v7 max, 3 sub-packets\n\
+             18..29   v2 literal 1\n\
+             29..40   v4 literal 2\n\
+             40..51   v1 literal 3"
+        );
+
+        let (_, layout) =
+            Packet::parse_layout(&mut BitStream::from_hex("8A004A801A8002F478").unwrap()).unwrap();
+        assert_eq!(
+            layout.iter().map(|l| l.depth).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert!(layout
+            .windows(2)
+            .all(|w| w[0].bits.start < w[1].bits.start && w[0].bits.end >= w[1].bits.end));
+    }
+
+    #[test]
+    fn encodes_packets() {
+        assert_eq!(literal(6, 2021).to_hex(LengthType::Bits), "D2FE28");
+        assert_eq!(literal(0, 0).to_hex(LengthType::Bits), "1000");
+
+        for (hex, length_type) in [
+            ("38006F45291200", LengthType::Bits),
+            ("EE00D40C823060", LengthType::Count),
+        ] {
+            assert_eq!(packet(hex).to_hex(length_type), hex);
+        }
+
+        // Too many sub-packets to count on 11 bits
+        let many = Packet {
+            version: 0,
+            ptype: 0,
+            payload: Payload::Operator(vec![literal(0, 0); 2048]),
+        };
+        assert!(many.to_hex(LengthType::Count).starts_with("016000"));
+        assert_eq!(packet(&many.to_hex(LengthType::Count)), many);
+    }
+
+    fn packets() -> impl Strategy<Value = Packet> {
+        let literals =
+            (0..8u32, any::<u64>()).prop_map(|(version, n)| Packet::literal(version, n).unwrap());
+
+        literals.prop_recursive(4, 64, 4, |packets| {
+            let operator = |(version, ptype, pkts)| Packet::operator(version, ptype, pkts).unwrap();
+
+            prop_oneof![
+                (
+                    0..8u32,
+                    0..4u32,
+                    prop::collection::vec(packets.clone(), 1..=4)
+                )
+                    .prop_map(operator),
+                (0..8u32, 5..8u32, prop::collection::vec(packets, 2)).prop_map(operator),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn round_trips(p in packets(), by_count in any::<bool>()) {
+            let length_type = if by_count { LengthType::Count } else { LengthType::Bits };
+
+            prop_assert_eq!(&decode(&p.to_hex(length_type)), &Ok(p.clone()));
+
+            let mut writer = BitWriter::new();
+            p.encode(&mut writer, length_type);
+            prop_assert_eq!(Packet::parse(&mut BitStream::from_bytes(writer.as_bytes())), Ok(p));
+        }
+
+        #[test]
+        fn decodes_anything_without_panicking(hex in "[0-9A-F]{0,64}") {
+            let _ = decode(&hex).and_then(|p| p.value());
+        }
     }
 }
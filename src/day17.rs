@@ -1,28 +1,44 @@
 use std::ops::RangeInclusive;
 
-pub type Target = (RangeInclusive<i32>, RangeInclusive<i32>);
+use nom::{
+    combinator::map,
+    sequence::{preceded, separated_pair, tuple},
+};
 
-pub fn parse_input(input: &str) -> Target {
-    let (x, y) = input.split_once(", ").unwrap();
+use crate::{
+    parse::{self, number, token, IResult},
+    ParseError, Solution,
+};
 
-    let (xs, xe) = x[15..].split_once("..").unwrap();
-    let (ys, ye) = y[2..].split_once("..").unwrap();
+pub type Target = (RangeInclusive<i32>, RangeInclusive<i32>);
 
-    (
-        xs.parse().unwrap()..=xe.parse().unwrap(),
-        ys.parse().unwrap()..=ye.parse().unwrap(),
+pub fn parse_input(input: &str) -> Result<Target, ParseError> {
+    parse::run(
+        Solver::DAY,
+        input,
+        preceded(
+            tuple((token("target area:"), token("x="))),
+            separated_pair(range, tuple((token(","), token("y="))), range),
+        ),
     )
 }
 
-pub fn part_1(tgt: Target) -> i32 {
+fn range(input: &str) -> IResult<'_, RangeInclusive<i32>> {
+    map(
+        separated_pair(number, token(".."), number),
+        |(start, end)| start..=end,
+    )(input)
+}
+
+pub fn part_1(tgt: &Target) -> i32 {
     solve(tgt).0
 }
 
-pub fn part_2(tgt: Target) -> u32 {
+pub fn part_2(tgt: &Target) -> u32 {
     solve(tgt).1
 }
 
-pub fn solve(tgt: Target) -> (i32, u32) {
+pub fn solve(tgt: &Target) -> (i32, u32) {
     let mut valid_solutions = 0;
     let mut y_max = 0;
 
@@ -49,7 +65,7 @@ pub fn solve(tgt: Target) -> (i32, u32) {
             while pos.1 > *tgt.1.start() {
                 step(&mut pos, &mut vel);
 
-                if in_target(pos, &tgt) {
+                if in_target(pos, tgt) {
                     valid_solutions += 1;
                     y_max = y_max.max(vy * (vy + 1) / 2);
                     break;
@@ -77,13 +93,38 @@ fn in_target(pos: (i32, i32), target: &Target) -> bool {
     target.0.contains(&pos.0) && target.1.contains(&pos.1)
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 17;
+
+    type Input<'a> = Target;
+    type Part1 = i32;
+    type Part2 = u32;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "target area: x=20..30, y=-10..-5";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day17.txt"))),
-        4186
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        45
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day17.txt"))),
-        2709
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        112
     }
 }
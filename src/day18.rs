@@ -1,7 +1,19 @@
-use std::{iter::Sum, ops::Add};
+use std::{
+    fmt,
+    iter::{Peekable, Sum},
+    ops::Add,
+    slice,
+    str::FromStr,
+    thread,
+};
 
 use itertools::Itertools;
 
+use crate::{
+    parse::{self, expect, lines, number, token, Expected, IResult},
+    ParseError, Solution,
+};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Number {
     data: Vec<(u64, i32)>,
@@ -38,13 +50,40 @@ impl Number {
         stack.pop().unwrap().0
     }
 
+    /// The pair of `left` and `right`, before any reduction.
+    pub fn pair(left: Self, right: Self) -> Self {
+        let mut data = left.data;
+        data.extend(right.data);
+
+        for (_, d) in &mut data {
+            *d += 1;
+        }
+
+        Number { data }
+    }
+
+    /// Reduces the number one action at a time, going through the number after each action.
+    pub fn reduction(self) -> Reduction {
+        Reduction { number: self }
+    }
+
     fn reduce(&mut self) {
         // Keep going until self either can be exploded (first) or split (second)
-        while self.explode().is_some() || self.split().is_some() {}
+        while self.step().is_some() {}
+    }
+
+    /// Applies the next reduction action, if any.
+    fn step(&mut self) -> Option<Action> {
+        self.explode().or_else(|| self.split())
     }
 
-    fn explode(&mut self) -> Option<()> {
+    fn explode(&mut self) -> Option<Action> {
         let (i, &(_, depth)) = self.data.iter().find_position(|(_, depth)| *depth >= 4)?;
+        let action = Action::Explode {
+            position: i,
+            left: self.data[i].0,
+            right: self.data[i + 1].0,
+        };
 
         if i > 0 {
             self.data[i - 1].0 += self.data[i].0;
@@ -55,32 +94,113 @@ impl Number {
 
         self.data.splice(i..i + 2, [(0, depth - 1)]);
 
-        Some(())
+        Some(action)
     }
 
-    fn split(&mut self) -> Option<()> {
+    fn split(&mut self) -> Option<Action> {
         let (i, &(num, depth)) = self.data.iter().find_position(|(n, _)| *n >= 10)?;
 
-        self.data
-            .splice(i..i + 1, [(num / 2, depth + 1), ((num + 1) / 2, depth + 1)]);
+        self.data.splice(
+            i..i + 1,
+            [(num / 2, depth + 1), (num.div_ceil(2), depth + 1)],
+        );
+
+        Some(Action::Split {
+            position: i,
+            value: num,
+        })
+    }
+}
+
+/// An action taken while reducing a number. Positions count regular numbers from the left,
+/// starting from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// The pair `[left,right]`, whose left number was at `position`, exploded.
+    Explode {
+        position: usize,
+        left: u64,
+        right: u64,
+    },
+    /// The regular number `value` at `position` split.
+    Split { position: usize, value: u64 },
+}
 
-        Some(())
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Explode { left, right, .. } => write!(f, "explode [{},{}]", left, right),
+            Action::Split { value, .. } => write!(f, "split {}", value),
+        }
+    }
+}
+
+/// Iterator over the steps of the reduction of a number, as the action taken along with the
+/// number right after it. See [`Number::reduction`].
+#[derive(Debug, Clone)]
+pub struct Reduction {
+    number: Number,
+}
+
+impl Iterator for Reduction {
+    type Item = (Action, Number);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let action = self.number.step()?;
+        Some((action, self.number.clone()))
     }
 }
 
 impl Add for Number {
     type Output = Self;
 
-    fn add(mut self, rhs: Self) -> Self::Output {
-        for (_, d) in &mut self.data {
-            *d += 1;
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut sum = Number::pair(self, rhs);
+        sum.reduce();
+        sum
+    }
+}
+
+/// Writes the number back in its puzzle form, e.g. `[[1,2],3]`.
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn pair(
+            f: &mut fmt::Formatter<'_>,
+            data: &mut Peekable<slice::Iter<(u64, i32)>>,
+            depth: i32,
+        ) -> fmt::Result {
+            write!(f, "[")?;
+            element(f, data, depth)?;
+            write!(f, ",")?;
+            element(f, data, depth)?;
+            write!(f, "]")
         }
-        for (n, d) in rhs.data {
-            self.data.push((n, d + 1));
+
+        // Literals deeper than `depth` belong to a nested pair
+        fn element(
+            f: &mut fmt::Formatter<'_>,
+            data: &mut Peekable<slice::Iter<(u64, i32)>>,
+            depth: i32,
+        ) -> fmt::Result {
+            match data.peek() {
+                Some(&&(n, d)) if d == depth => {
+                    data.next();
+                    write!(f, "{}", n)
+                }
+                Some(_) => pair(f, data, depth + 1),
+                None => Err(fmt::Error),
+            }
         }
 
-        self.reduce();
-        self
+        pair(f, &mut self.data.iter().peekable(), 0)
+    }
+}
+
+impl FromStr for Number {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse::run(Solver::DAY, s, snailfish)
     }
 }
 
@@ -94,47 +214,612 @@ impl Sum for Number {
     }
 }
 
-pub fn parse_input(input: &str) -> Vec<Number> {
-    input
-        .lines()
-        .map(|line| {
-            let mut data = Vec::new();
-            let mut depth = -1;
+/// A snailfish number as a binary tree, with its nodes stored in an arena. Unlike the flat
+/// [`Number`], exploding a pair or splitting a regular number only walks up and down the tree.
+#[derive(Debug, Clone)]
+pub struct Tree {
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    parent: Option<usize>,
+    /// Number of pairs the node is nested in.
+    depth: usize,
+    element: Element,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Element {
+    Regular(u64),
+    /// Indices of the left and right elements of the pair.
+    Pair(usize, usize),
+}
+
+impl Tree {
+    pub fn magnitude(&self) -> u64 {
+        self.magnitude_of(self.root)
+    }
+
+    fn magnitude_of(&self, id: usize) -> u64 {
+        match self.nodes[id].element {
+            Element::Regular(n) => n,
+            Element::Pair(l, r) => 3 * self.magnitude_of(l) + 2 * self.magnitude_of(r),
+        }
+    }
+
+    /// The pair of `left` and `right`, before any reduction. Nodes left behind by previous
+    /// reductions aren't carried over.
+    pub fn pair(left: &Tree, right: &Tree) -> Self {
+        let mut tree = Tree {
+            nodes: Vec::with_capacity(left.nodes.len() + right.nodes.len() + 1),
+            root: 0,
+        };
+
+        tree.push(None, 0, Element::Pair(0, 0));
+        let l = tree.graft(left, left.root, 0);
+        let r = tree.graft(right, right.root, 0);
+        tree.nodes[0].element = Element::Pair(l, r);
+
+        tree
+    }
 
-            for c in line.chars() {
-                match c {
-                    '[' => depth += 1,
-                    ']' => depth -= 1,
-                    ',' => (),
-                    _ => data.push((c.to_digit(10).unwrap() as u64, depth)),
+    /// Copies the element `id` of `from`, and everything below it, under `parent`.
+    fn graft(&mut self, from: &Tree, id: usize, parent: usize) -> usize {
+        let depth = self.nodes[parent].depth + 1;
+        let new = self.push(Some(parent), depth, from.nodes[id].element);
+
+        if let Element::Pair(l, r) = from.nodes[id].element {
+            let l = self.graft(from, l, new);
+            let r = self.graft(from, r, new);
+            self.nodes[new].element = Element::Pair(l, r);
+        }
+
+        new
+    }
+
+    fn push(&mut self, parent: Option<usize>, depth: usize, element: Element) -> usize {
+        self.nodes.push(Node {
+            parent,
+            depth,
+            element,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// The first regular number of the element `id`.
+    fn leftmost(&self, mut id: usize) -> usize {
+        while let Element::Pair(l, _) = self.nodes[id].element {
+            id = l;
+        }
+        id
+    }
+
+    /// The last regular number of the element `id`.
+    fn rightmost(&self, mut id: usize) -> usize {
+        while let Element::Pair(_, r) = self.nodes[id].element {
+            id = r;
+        }
+        id
+    }
+
+    /// The first regular number to the left of the element `id`.
+    fn prev_regular(&self, mut id: usize) -> Option<usize> {
+        loop {
+            let parent = self.nodes[id].parent?;
+            match self.nodes[parent].element {
+                Element::Pair(l, r) if r == id => return Some(self.rightmost(l)),
+                _ => id = parent,
+            }
+        }
+    }
+
+    /// The first regular number to the right of the element `id`.
+    fn next_regular(&self, mut id: usize) -> Option<usize> {
+        loop {
+            let parent = self.nodes[id].parent?;
+            match self.nodes[parent].element {
+                Element::Pair(l, r) if l == id => return Some(self.leftmost(r)),
+                _ => id = parent,
+            }
+        }
+    }
+
+    /// The two regular numbers of `id`, if it's a pair nested in four pairs.
+    fn explodable(&self, id: usize) -> Option<(u64, u64)> {
+        let Element::Pair(l, r) = self.nodes[id].element else {
+            return None;
+        };
+
+        match (self.nodes[l].element, self.nodes[r].element) {
+            (Element::Regular(a), Element::Regular(b)) if self.nodes[id].depth >= 4 => Some((a, b)),
+            _ => None,
+        }
+    }
+
+    fn explode(&mut self, id: usize, (a, b): (u64, u64)) {
+        if let Some(prev) = self.prev_regular(id) {
+            self.add_to(prev, a);
+        }
+        if let Some(next) = self.next_regular(id) {
+            self.add_to(next, b);
+        }
+
+        self.nodes[id].element = Element::Regular(0);
+    }
+
+    fn split(&mut self, id: usize, n: u64) {
+        let depth = self.nodes[id].depth + 1;
+        let l = self.push(Some(id), depth, Element::Regular(n / 2));
+        let r = self.push(Some(id), depth, Element::Regular(n.div_ceil(2)));
+        self.nodes[id].element = Element::Pair(l, r);
+    }
+
+    fn add_to(&mut self, id: usize, n: u64) {
+        if let Element::Regular(ref mut m) = self.nodes[id].element {
+            *m += n;
+        }
+    }
+
+    /// Applies the same actions as [`Number::reduce`], but picks up where the previous action
+    /// happened rather than looking for the next one from the start.
+    fn reduce(&mut self) {
+        // Explosions only add to regular numbers, so they can't make pairs on their left explode
+        let mut next = Some(self.leftmost(self.root));
+        while let Some(id) = next {
+            match self.nodes[id].parent {
+                Some(pair) => match self.explodable(pair) {
+                    Some(regulars) => {
+                        self.explode(pair, regulars);
+                        next = Some(pair);
+                    }
+                    None => next = self.next_regular(id),
+                },
+                None => next = None,
+            }
+        }
+
+        // Splits that end up too deep explode right away, which may take the regular number on
+        // their left past 9, so the search carries on from there
+        let mut next = Some(self.leftmost(self.root));
+        while let Some(id) = next {
+            match self.nodes[id].element {
+                Element::Regular(n) if n >= 10 => {
+                    self.split(id, n);
+
+                    match self.explodable(id) {
+                        Some(regulars) => {
+                            let prev = self.prev_regular(id);
+                            self.explode(id, regulars);
+                            next = prev.or(Some(id));
+                        }
+                        None => next = Some(self.leftmost(id)),
+                    }
                 }
+                _ => next = self.next_regular(id),
             }
+        }
+    }
 
-            Number { data }
-        })
-        .collect()
+    /// Builds the pair at `depth` from the start of a flat number.
+    fn build(
+        &mut self,
+        data: &mut Peekable<slice::Iter<(u64, i32)>>,
+        depth: i32,
+        parent: Option<usize>,
+    ) -> usize {
+        let id = self.push(parent, depth as usize, Element::Pair(0, 0));
+        let l = self.build_element(data, depth, id);
+        let r = self.build_element(data, depth, id);
+        self.nodes[id].element = Element::Pair(l, r);
+        id
+    }
+
+    fn build_element(
+        &mut self,
+        data: &mut Peekable<slice::Iter<(u64, i32)>>,
+        depth: i32,
+        parent: usize,
+    ) -> usize {
+        // Regular numbers deeper than `depth` belong to a nested pair
+        if data.peek().is_some_and(|&&(_, d)| d > depth) {
+            self.build(data, depth + 1, Some(parent))
+        } else {
+            let n = data.next().map_or(0, |&(n, _)| n);
+            self.push(Some(parent), depth as usize + 1, Element::Regular(n))
+        }
+    }
+
+    fn flatten(&self, id: usize, depth: i32, data: &mut Vec<(u64, i32)>) {
+        match self.nodes[id].element {
+            Element::Regular(n) => data.push((n, depth - 1)),
+            Element::Pair(l, r) => {
+                self.flatten(l, depth + 1, data);
+                self.flatten(r, depth + 1, data);
+            }
+        }
+    }
+}
+
+impl From<&Number> for Tree {
+    fn from(number: &Number) -> Self {
+        let mut tree = Tree {
+            nodes: Vec::with_capacity(2 * number.data.len()),
+            root: 0,
+        };
+        tree.root = tree.build(&mut number.data.iter().peekable(), 0, None);
+        tree
+    }
+}
+
+impl From<&Tree> for Number {
+    fn from(tree: &Tree) -> Self {
+        let mut data = Vec::new();
+        tree.flatten(tree.root, 0, &mut data);
+        Number { data }
+    }
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn element(tree: &Tree, f: &mut fmt::Formatter<'_>, id: usize) -> fmt::Result {
+            match tree.nodes[id].element {
+                Element::Regular(n) => write!(f, "{}", n),
+                Element::Pair(l, r) => {
+                    write!(f, "[")?;
+                    element(tree, f, l)?;
+                    write!(f, ",")?;
+                    element(tree, f, r)?;
+                    write!(f, "]")
+                }
+            }
+        }
+
+        element(self, f, self.root)
+    }
+}
+
+impl Add<&Tree> for &Tree {
+    type Output = Tree;
+
+    fn add(self, rhs: &Tree) -> Self::Output {
+        let mut sum = Tree::pair(self, rhs);
+        sum.reduce();
+        sum
+    }
+}
+
+impl Add for Tree {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
 }
 
-pub fn part_1(numbers: Vec<Number>) -> u64 {
-    numbers.into_iter().sum::<Number>().magnitude()
+impl Sum for Tree {
+    fn sum<I: Iterator<Item = Self>>(mut iter: I) -> Self {
+        let mut lhs = iter.next().unwrap();
+        for rhs in iter {
+            lhs = lhs + rhs;
+        }
+        lhs
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Number>, ParseError> {
+    parse::run(Solver::DAY, input, lines(snailfish))
+}
+
+fn snailfish(input: &str) -> IResult<'_, Number> {
+    let mut data = Vec::new();
+    let (rest, _) = pair(&mut data, 0, input)?;
+
+    Ok((rest, Number { data }))
 }
 
-pub fn part_2(numbers: Vec<Number>) -> u64 {
-    numbers
-        .iter()
-        .permutations(2)
-        .map(|nums| (nums[0].clone() + nums[1].clone()).magnitude())
-        .max()
-        .unwrap()
+fn pair<'a>(data: &mut Vec<(u64, i32)>, depth: i32, input: &'a str) -> IResult<'a, ()> {
+    let (input, _) = token("[")(input)?;
+    let (input, _) = element(data, depth, input)?;
+    let (input, _) = token(",")(input)?;
+    let (input, _) = element(data, depth, input)?;
+    token("]")(input).map(|(rest, _)| (rest, ()))
+}
+
+fn element<'a>(data: &mut Vec<(u64, i32)>, depth: i32, input: &'a str) -> IResult<'a, ()> {
+    if input.starts_with(|c: char| c.is_ascii_digit()) {
+        let (rest, n) = number(input)?;
+        data.push((n, depth));
+        Ok((rest, ()))
+    } else {
+        expect(Expected::Description("a number or a pair"), |input| {
+            pair(data, depth + 1, input)
+        })(input)
+    }
+}
+
+pub fn part_1(numbers: &[Number]) -> u64 {
+    numbers.iter().map(Tree::from).sum::<Tree>().magnitude()
+}
+
+pub fn part_2(numbers: &[Number]) -> u64 {
+    let trees = numbers.iter().map(Tree::from).collect::<Vec<_>>();
+    let threads = thread::available_parallelism().map_or(1, usize::from);
+
+    // Every thread tries every `threads`-th number on the left of all the others
+    thread::scope(|s| {
+        let workers = (0..threads)
+            .map(|first| {
+                let trees = &trees;
+                s.spawn(move || {
+                    (first..trees.len())
+                        .step_by(threads)
+                        .flat_map(|i| {
+                            (0..trees.len())
+                                .filter(move |&j| i != j)
+                                .map(move |j| (&trees[i] + &trees[j]).magnitude())
+                        })
+                        .max()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .filter_map(|worker| worker.join().unwrap())
+            .max()
+            .unwrap()
+    })
+}
+
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 18;
+
+    type Input<'a> = Vec<Number>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
 }
 
+#[cfg(test)]
+const EXAMPLE: &str = "\
+[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day18.txt"))),
-        4469
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        4140
     },
-    p2=> {
-        part_2(parse_input(include_str!("../inputs/day18.txt"))),
-        4770
+    p2 => {
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        3993
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn number(s: &str) -> Number {
+        s.parse().unwrap()
+    }
+
+    fn exploded(s: &str) -> Number {
+        let mut n = number(s);
+        n.explode().unwrap();
+        n
+    }
+
+    #[test]
+    fn explode() {
+        for (before, after) in [
+            ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+            ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+            ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+            (
+                "[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]",
+                "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]",
+            ),
+            (
+                "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]",
+                "[[3,[2,[8,0]]],[9,[5,[7,0]]]]",
+            ),
+        ] {
+            assert_eq!(exploded(before).to_string(), after, "exploding {}", before);
+        }
+    }
+
+    #[test]
+    fn reduction_steps() {
+        let sum = Number::pair(number("[[[[4,3],4],4],[7,[[8,4],9]]]"), number("[1,1]"));
+        assert_eq!(sum.to_string(), "[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+
+        let steps = sum
+            .clone()
+            .reduction()
+            .map(|(action, n)| (action.to_string(), n.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            steps,
+            [
+                ("explode [4,3]", "[[[[0,7],4],[7,[[8,4],9]]],[1,1]]"),
+                ("explode [8,4]", "[[[[0,7],4],[15,[0,13]]],[1,1]]"),
+                ("split 15", "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]"),
+                ("split 13", "[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]"),
+                ("explode [6,7]", "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"),
+            ]
+            .map(|(action, n)| (action.to_string(), n.to_string()))
+        );
+
+        // Splits only happen once there's nothing left to explode
+        assert_eq!(
+            sum.reduction()
+                .map(|(action, _)| action)
+                .collect::<Vec<_>>(),
+            [
+                Action::Explode {
+                    position: 0,
+                    left: 4,
+                    right: 3
+                },
+                Action::Explode {
+                    position: 4,
+                    left: 8,
+                    right: 4
+                },
+                Action::Split {
+                    position: 3,
+                    value: 15
+                },
+                Action::Split {
+                    position: 6,
+                    value: 13
+                },
+                Action::Explode {
+                    position: 6,
+                    left: 6,
+                    right: 7
+                },
+            ]
+        );
+
+        assert_eq!(
+            (number("[[[[4,3],4],4],[7,[[8,4],9]]]") + number("[1,1]")).to_string(),
+            "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"
+        );
+    }
+
+    #[test]
+    fn sums() {
+        let sum = |n: usize| {
+            (1..=n)
+                .map(|i| number(&format!("[{},{}]", i, i)))
+                .sum::<Number>()
+        };
+
+        assert_eq!(sum(4).to_string(), "[[[[1,1],[2,2]],[3,3]],[4,4]]");
+        assert_eq!(sum(5).to_string(), "[[[[3,0],[5,3]],[4,4]],[5,5]]");
+        assert_eq!(sum(6).to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+
+        assert_eq!(
+            parse_input(EXAMPLE)
+                .unwrap()
+                .into_iter()
+                .sum::<Number>()
+                .to_string(),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
+    }
+
+    #[test]
+    fn magnitudes() {
+        for (n, magnitude) in [
+            ("[[1,2],[[3,4],5]]", 143),
+            ("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", 1384),
+            ("[[[[1,1],[2,2]],[3,3]],[4,4]]", 445),
+            ("[[[[3,0],[5,3]],[4,4]],[5,5]]", 791),
+            ("[[[[5,0],[7,4]],[5,5]],[6,6]]", 1137),
+            (
+                "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]",
+                3488,
+            ),
+        ] {
+            assert_eq!(number(n).magnitude(), magnitude, "magnitude of {}", n);
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for line in EXAMPLE.lines().chain([
+            "[[[[0,7],4],[15,[0,13]]],[1,1]]",
+            "[1234567890,[[[[0,1],2],3],4]]",
+        ]) {
+            assert_eq!(number(line).to_string(), line);
+        }
+
+        assert_eq!(
+            "[1,[2,3]".parse::<Number>().unwrap_err().to_string(),
+            "day 18, line 1, column 9: expected `]`"
+        );
+        assert!("[1,x]".parse::<Number>().is_err());
+    }
+
+    #[test]
+    fn trees() {
+        let numbers = parse_input(EXAMPLE).unwrap();
+        let trees = numbers.iter().map(Tree::from).collect::<Vec<_>>();
+
+        for (number, tree) in numbers.iter().zip(&trees) {
+            assert_eq!(tree.to_string(), number.to_string());
+            assert_eq!(&Number::from(tree), number);
+            assert_eq!(tree.magnitude(), number.magnitude());
+        }
+
+        // Reductions pick up where they left off, but must end up where the flat ones do
+        for (i, j) in (0..trees.len()).flat_map(|i| (0..trees.len()).map(move |j| (i, j))) {
+            let sum = &trees[i] + &trees[j];
+            let expected = numbers[i].clone() + numbers[j].clone();
+
+            assert_eq!(Number::from(&sum), expected, "sum of #{} and #{}", i, j);
+        }
+
+        let sum = trees.into_iter().sum::<Tree>();
+        assert_eq!(
+            sum.to_string(),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
+        assert_eq!(Number::from(&sum).to_string(), sum.to_string());
+        assert_eq!(
+            (&Tree::from(&number("[[[[4,3],4],4],[7,[[8,4],9]]]")) + &Tree::from(&number("[1,1]")))
+                .to_string(),
+            "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"
+        );
+    }
+
+    /// Reduced numbers, as text: pairs nested at most 4 deep, with regular numbers up to 9.
+    fn reduced_numbers() -> impl Strategy<Value = String> {
+        let element = (0..10u64)
+            .prop_map(|n| n.to_string())
+            .prop_recursive(3, 16, 2, |element| {
+                (element.clone(), element).prop_map(|(l, r)| format!("[{},{}]", l, r))
+            });
+
+        (element.clone(), element).prop_map(|(l, r)| format!("[{},{}]", l, r))
+    }
+
+    proptest! {
+        #[test]
+        fn tree_sums_match_flat_sums(a in reduced_numbers(), b in reduced_numbers()) {
+            let (a, b) = (number(&a), number(&b));
+            let sum = &Tree::from(&a) + &Tree::from(&b);
+
+            prop_assert_eq!(Number::from(&sum), a + b);
+        }
     }
 }
@@ -1,166 +1,593 @@
-use std::collections::{hash_map::RandomState, HashSet};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet, VecDeque},
+    ops::Mul,
+};
 
 use itertools::Itertools;
+use lazy_static::lazy_static;
+
+use nom::{
+    combinator::map,
+    sequence::{preceded, tuple},
+};
+
+use crate::{
+    parse::{self, lines, newline, number, sections, token, IResult},
+    ParseError, Solution,
+};
 
 pub type Coord = [i32; 3];
 
-pub fn parse_input(input: &str) -> Vec<Vec<Coord>> {
-    input
-        .split("\n\n")
-        .map(|chunk| {
-            chunk
-                .lines()
-                .skip(1)
-                .map(|l| {
-                    let mut nums = l.split(',');
-                    [
-                        nums.next().unwrap().parse().unwrap(),
-                        nums.next().unwrap().parse().unwrap(),
-                        nums.next().unwrap().parse().unwrap(),
-                    ]
-                })
-                .collect()
-        })
-        .collect()
+pub fn parse_input(input: &str) -> Result<Vec<Vec<Coord>>, ParseError> {
+    parse::run(Solver::DAY, input, sections(scanner))
 }
 
-pub fn part_1(input: Vec<Vec<Coord>>) -> usize {
-    solve(input).0
+fn scanner(input: &str) -> IResult<'_, Vec<Coord>> {
+    let header = tuple((
+        token("---"),
+        token("scanner"),
+        number::<usize>,
+        token("---"),
+        newline,
+    ));
+
+    preceded(header, lines(coord))(input)
 }
 
-pub fn part_2(input: Vec<Vec<Coord>>) -> i32 {
-    let offsets = solve(input).1;
+fn coord(input: &str) -> IResult<'_, Coord> {
+    map(
+        tuple((number, token(","), number, token(","), number)),
+        |(x, _, y, _, z)| [x, y, z],
+    )(input)
+}
 
-    offsets
-        .iter()
-        .combinations(2)
-        .map(|os| {
-            (os[0][0] - os[1][0]).abs() + (os[0][1] - os[1][1]).abs() + (os[0][2] - os[1][2]).abs()
-        })
-        .max()
-        .unwrap()
+/// Number of beacons two scanners must both see to be placed relative to each other.
+pub const OVERLAP: usize = 12;
+
+pub fn part_1(input: &[Vec<Coord>]) -> usize {
+    reconstruct(input, OVERLAP).beacons.len()
+}
+
+pub fn part_2(input: &[Vec<Coord>]) -> i32 {
+    reconstruct(input, OVERLAP).largest_distance()
 }
 
-fn solve(mut input: Vec<Vec<Coord>>) -> (usize, Vec<Coord>) {
-    let mut probes: HashSet<Coord, RandomState> = HashSet::from_iter(input[0].iter().cloned());
-    let mut offsets = vec![[0, 0, 0]; input.len()];
-    let mut discovered = vec![0];
+lazy_static! {
+    static ref ROTATIONS: Vec<Rotation> = Rotation::generate();
+}
+
+/// A rotation by quarter turns, as the matrix turning coordinates from one orientation to
+/// another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation([[i32; 3]; 3]);
+
+impl Rotation {
+    pub const IDENTITY: Self = Self([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    /// A quarter turn around the x axis, taking y to z.
+    pub const QUARTER_X: Self = Self([[1, 0, 0], [0, 0, -1], [0, 1, 0]]);
 
-    while discovered.len() < input.len() {
-        let mut found = None;
+    /// A quarter turn around the z axis, taking x to y.
+    pub const QUARTER_Z: Self = Self([[0, -1, 0], [1, 0, 0], [0, 0, 1]]);
 
-        'search: for &reference in discovered.iter().rev() {
-            let scanner = input[reference].clone();
+    /// The 24 orientations a scanner can be in, starting with the identity.
+    pub fn all() -> &'static [Rotation] {
+        &ROTATIONS
+    }
+
+    /// Turns the identity by quarter turns around x and z until no new rotation comes up, which
+    /// is enough to reach every orientation.
+    fn generate() -> Vec<Rotation> {
+        let mut rotations = vec![Self::IDENTITY];
+        let mut i = 0;
 
-            for (i, sensor) in input.iter_mut().enumerate() {
-                if discovered.contains(&i) {
-                    continue;
+        while let Some(&rotation) = rotations.get(i) {
+            for turn in [Self::QUARTER_X, Self::QUARTER_Z] {
+                let next = turn * rotation;
+                if !rotations.contains(&next) {
+                    rotations.push(next);
                 }
+            }
+            i += 1;
+        }
+
+        rotations
+    }
+
+    pub fn matrix(&self) -> [[i32; 3]; 3] {
+        self.0
+    }
+
+    pub fn transform(&self, coord: Coord) -> Coord {
+        self.0
+            .map(|row| row[0] * coord[0] + row[1] * coord[1] + row[2] * coord[2])
+    }
+
+    /// The rotation undoing this one, whose matrix is the transpose of this one's.
+    pub fn inverse(&self) -> Self {
+        Self(std::array::from_fn(|i| {
+            std::array::from_fn(|j| self.0[j][i])
+        }))
+    }
+
+    pub fn determinant(&self) -> i32 {
+        let m = &self.0;
+
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+}
+
+/// Composes two rotations: `a * b` turns coordinates by `b`, then by `a`.
+impl Mul for Rotation {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(std::array::from_fn(|i| {
+            std::array::from_fn(|j| (0..3).map(|k| self.0[i][k] * rhs.0[k][j]).sum())
+        }))
+    }
+}
+
+/// Where a scanner sits, relative to scanner 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub position: Coord,
+    /// Turns the readings of the scanner into the orientation of scanner 0.
+    pub rotation: Rotation,
+}
+
+impl Placement {
+    /// Where a beacon seen by the scanner at `reading` is, relative to scanner 0.
+    pub fn locate(&self, reading: Coord) -> Coord {
+        let [x, y, z] = self.rotation.transform(reading);
+
+        [
+            x + self.position[0],
+            y + self.position[1],
+            z + self.position[2],
+        ]
+    }
+}
+
+/// The map of the trench pieced together from the scanner reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map {
+    /// Placement of every scanner, in the order of the reports. Scanners that don't see enough
+    /// beacons in common with any of the placed ones are left out.
+    pub scanners: Vec<Option<Placement>>,
+    /// Every beacon seen by any scanner, relative to scanner 0, in ascending order.
+    pub beacons: Vec<Coord>,
+}
+
+impl Map {
+    /// Largest Manhattan distance between any two scanners.
+    pub fn largest_distance(&self) -> i32 {
+        self.scanners
+            .iter()
+            .flatten()
+            .tuple_combinations()
+            .map(|(a, b)| {
+                (0..3)
+                    .map(|i| (a.position[i] - b.position[i]).abs())
+                    .sum::<i32>()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Places as many scanners as possible relative to scanner 0, by finding pairs of scanners with
+/// at least `overlap` beacons in common.
+pub fn reconstruct(scanners: &[Vec<Coord>], overlap: usize) -> Map {
+    let prints = scanners
+        .iter()
+        .map(|readings| fingerprints(readings))
+        .collect::<Vec<_>>();
+
+    // Beacons seen by both scanners of a pair make for this many distances in common
+    let shared_distances = overlap * overlap.saturating_sub(1) / 2;
 
-                for tran in TRANSFORMATIONS {
-                    let transformed = transform(sensor, tran);
-
-                    for origin in &scanner {
-                        for pivot in &transformed {
-                            let offset = [
-                                origin[0] - pivot[0],
-                                origin[1] - pivot[1],
-                                origin[2] - pivot[2],
-                            ];
-
-                            let matching = transformed
-                                .iter()
-                                .filter(|elem| {
-                                    scanner.contains(&[
-                                        elem[0] + offset[0],
-                                        elem[1] + offset[1],
-                                        elem[2] + offset[2],
-                                    ])
-                                })
-                                .count();
-
-                            if matching >= 12 {
-                                let offset_from_zero = [
-                                    offset[0] + offsets[reference][0],
-                                    offset[1] + offsets[reference][1],
-                                    offset[2] + offsets[reference][2],
-                                ];
-
-                                for x in &transformed {
-                                    probes.insert([
-                                        x[0] + offset_from_zero[0],
-                                        x[1] + offset_from_zero[1],
-                                        x[2] + offset_from_zero[2],
-                                    ]);
-                                }
-
-                                offsets[i] = offset_from_zero;
-                                *sensor = transformed;
-                                found = Some(i);
-
-                                break 'search;
-                            }
-                        }
+    let mut placements = vec![None; scanners.len()];
+    // Beacons of the placed scanners, relative to scanner 0
+    let mut located = vec![Vec::new(); scanners.len()];
+    let mut queue = VecDeque::new();
+
+    if !scanners.is_empty() {
+        placements[0] = Some(Placement {
+            position: [0, 0, 0],
+            rotation: Rotation::IDENTITY,
+        });
+        located[0] = scanners[0].clone();
+        queue.push_back(0);
+    }
+
+    while let Some(placed) = queue.pop_front() {
+        for other in 0..scanners.len() {
+            if placements[other].is_some()
+                || shared(&prints[placed], &prints[other]) < shared_distances
+            {
+                continue;
+            }
+
+            let Some(placement) = align(
+                (&located[placed], &prints[placed]),
+                (&scanners[other], &prints[other]),
+                overlap,
+            ) else {
+                continue;
+            };
+
+            located[other] = scanners[other]
+                .iter()
+                .map(|&reading| placement.locate(reading))
+                .collect();
+            placements[other] = Some(placement);
+            queue.push_back(other);
+        }
+    }
+
+    Map {
+        scanners: placements,
+        beacons: located.into_iter().flatten().sorted().dedup().collect(),
+    }
+}
+
+/// The distance between two beacons seen by a scanner, as the absolute differences along each
+/// axis in ascending order. It doesn't depend on how the scanner is oriented.
+type Fingerprint = [i32; 3];
+
+/// The pairs of readings of a scanner with each fingerprint.
+type Fingerprints = HashMap<Fingerprint, Vec<(usize, usize)>>;
+
+/// The fingerprints of every pair of beacons seen by a scanner.
+fn fingerprints(readings: &[Coord]) -> Fingerprints {
+    let mut prints = HashMap::<_, Vec<_>>::new();
+
+    for (i, j) in (0..readings.len()).tuple_combinations() {
+        let mut print = delta(readings[i], readings[j]).map(i32::abs);
+        print.sort_unstable();
+        prints.entry(print).or_default().push((i, j));
+    }
+
+    prints
+}
+
+/// Number of pairs of beacons with the same fingerprint in both scanners.
+fn shared(a: &Fingerprints, b: &Fingerprints) -> usize {
+    a.iter()
+        .filter_map(|(print, pairs)| Some(pairs.len().min(b.get(print)?.len())))
+        .sum()
+}
+
+/// Places the scanner with the readings `b` so that at least `overlap` of them land on the
+/// already located beacons `a`. Every pair of beacons with the same fingerprint in both votes for
+/// the rotation and position that would line them up, and the most popular ones are checked
+/// first.
+fn align(
+    (a, a_prints): (&[Coord], &Fingerprints),
+    (b, b_prints): (&[Coord], &Fingerprints),
+    overlap: usize,
+) -> Option<Placement> {
+    let mut votes = HashMap::<_, usize>::new();
+
+    for (print, a_pairs) in a_prints {
+        let Some(b_pairs) = b_prints.get(print) else {
+            continue;
+        };
+
+        for (&(a1, a2), &(b1, b2)) in a_pairs.iter().cartesian_product(b_pairs) {
+            let target = delta(a[a1], a[a2]);
+
+            // Either end of the pair may be the first one
+            for (b1, b2) in [(b1, b2), (b2, b1)] {
+                let from = delta(b[b1], b[b2]);
+
+                for &rotation in Rotation::all() {
+                    if rotation.transform(from) == target {
+                        let position = delta(rotation.transform(b[b1]), a[a1]);
+                        *votes.entry((rotation, position)).or_default() += 1;
                     }
                 }
             }
         }
+    }
 
-        discovered.push(found.unwrap());
-    }
-
-    (probes.len(), offsets)
-}
-
-fn transform(input: &[Coord], transform: [i32; 6]) -> Vec<Coord> {
-    let mut res = Vec::with_capacity(input.len());
-
-    for coord in input {
-        res.push([
-            coord[transform[0] as usize] * transform[3],
-            coord[transform[1] as usize] * transform[4],
-            coord[transform[2] as usize] * transform[5],
-        ]);
-    }
-
-    res
-}
-
-// ¯\_(ツ)_/¯
-const TRANSFORMATIONS: [[i32; 6]; 24] = [
-    [0, 1, 2, 1, 1, 1],
-    [0, 2, 1, 1, 1, -1],
-    [0, 1, 2, 1, -1, -1],
-    [0, 2, 1, 1, -1, 1],
-    [0, 1, 2, -1, 1, -1],
-    [0, 2, 1, -1, 1, 1],
-    [0, 1, 2, -1, -1, 1],
-    [0, 2, 1, -1, -1, -1],
-    [1, 0, 2, 1, 1, -1],
-    [1, 2, 0, 1, -1, -1],
-    [1, 0, 2, 1, -1, 1],
-    [1, 2, 0, 1, 1, 1],
-    [1, 0, 2, -1, 1, 1],
-    [1, 2, 0, -1, 1, -1],
-    [1, 0, 2, -1, -1, -1],
-    [1, 2, 0, -1, -1, 1],
-    [2, 1, 0, 1, 1, -1],
-    [2, 0, 1, 1, -1, -1],
-    [2, 1, 0, 1, -1, 1],
-    [2, 0, 1, 1, 1, 1],
-    [2, 1, 0, -1, 1, 1],
-    [2, 0, 1, -1, 1, -1],
-    [2, 1, 0, -1, -1, -1],
-    [2, 0, 1, -1, -1, 1],
-];
+    let known = a.iter().collect::<HashSet<_>>();
+
+    votes
+        .into_iter()
+        .sorted_by_key(|&(_, count)| Reverse(count))
+        .map(|((rotation, position), _)| Placement { position, rotation })
+        .find(|placement| {
+            b.iter()
+                .filter(|&&reading| known.contains(&placement.locate(reading)))
+                .count()
+                >= overlap
+        })
+}
+
+/// The vector going from `a` to `b`.
+fn delta(a: Coord, b: Coord) -> Coord {
+    [b[0] - a[0], b[1] - a[1], b[2] - a[2]]
+}
+
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 19;
+
+    type Input<'a> = Vec<Vec<Coord>>;
+    type Part1 = usize;
+    type Part2 = i32;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+--- scanner 0 ---
+404,-588,-901
+528,-643,409
+-838,591,734
+390,-675,-793
+-537,-823,-458
+-485,-357,347
+-345,-311,381
+-661,-816,-575
+-876,649,763
+-618,-824,-621
+553,345,-567
+474,580,667
+-447,-329,318
+-584,868,-557
+544,-627,-890
+564,392,-477
+455,729,728
+-892,524,684
+-689,845,-530
+423,-701,434
+7,-33,-71
+630,319,-379
+443,580,662
+-789,900,-551
+459,-707,401
+
+--- scanner 1 ---
+686,422,578
+605,423,415
+515,917,-361
+-336,658,858
+95,138,22
+-476,619,847
+-340,-569,-846
+567,-361,727
+-460,603,-452
+669,-402,600
+729,430,532
+-500,-761,534
+-322,571,750
+-466,-666,-811
+-429,-592,574
+-355,545,-477
+703,-491,-529
+-328,-685,520
+413,935,-424
+-391,539,-444
+586,-435,557
+-364,-763,-893
+807,-499,-711
+755,-354,-619
+553,889,-390
+
+--- scanner 2 ---
+649,640,665
+682,-795,504
+-784,533,-524
+-644,584,-595
+-588,-843,648
+-30,6,44
+-674,560,763
+500,723,-460
+609,671,-379
+-555,-800,653
+-675,-892,-343
+697,-426,-610
+578,704,681
+493,664,-388
+-671,-858,530
+-667,343,800
+571,-461,-707
+-138,-166,112
+-889,563,-600
+646,-828,498
+640,759,510
+-630,509,768
+-681,-892,-333
+673,-379,-804
+-742,-814,-386
+577,-820,562
+
+--- scanner 3 ---
+-589,542,597
+605,-692,669
+-500,565,-823
+-660,373,557
+-458,-679,-417
+-488,449,543
+-626,468,-788
+338,-750,-386
+528,-832,-391
+562,-778,733
+-938,-730,414
+543,643,-506
+-524,371,-870
+407,773,750
+-104,29,83
+378,-903,-323
+-778,-728,485
+426,699,580
+-438,-605,-362
+-469,-447,-387
+509,732,623
+647,635,-688
+-868,-804,481
+614,-800,639
+595,780,-596
+
+--- scanner 4 ---
+727,592,562
+-293,-554,779
+441,611,-461
+-714,465,-776
+-743,427,-804
+-660,-479,-426
+832,-632,460
+927,-485,-438
+408,393,-506
+466,436,-512
+110,16,151
+-258,-428,682
+-393,719,612
+-211,-452,876
+808,-476,-593
+-575,615,604
+-485,667,467
+-680,325,-822
+-627,-443,-432
+872,-547,-609
+833,512,582
+807,604,487
+839,-516,451
+891,-625,532
+-652,-548,-490
+30,-46,-14";
 
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day19.txt"))),
-        396
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        79
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day19.txt"))),
-        11828
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        3621
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotations() {
+        let rotations = Rotation::all();
+
+        assert_eq!(rotations.len(), 24);
+        assert_eq!(rotations.iter().collect::<HashSet<_>>().len(), 24);
+        assert_eq!(rotations[0], Rotation::IDENTITY);
+
+        // Exactly the axis permutations and sign flips that don't mirror
+        let proper = (0..3)
+            .permutations(3)
+            .cartesian_product((0..3).map(|_| [1, -1]).multi_cartesian_product())
+            .map(|(axes, signs)| {
+                let mut m = [[0; 3]; 3];
+                for i in 0..3 {
+                    m[i][axes[i]] = signs[i];
+                }
+                Rotation(m)
+            })
+            .filter(|r| r.determinant() == 1)
+            .collect::<HashSet<_>>();
+        assert_eq!(proper.len(), 24);
+        assert_eq!(rotations.iter().copied().collect::<HashSet<_>>(), proper);
+
+        let v = [1, 2, 3];
+        for &a in rotations {
+            assert_eq!(a.determinant(), 1);
+            assert_eq!(a * a.inverse(), Rotation::IDENTITY);
+            assert_eq!(a.inverse().transform(a.transform(v)), v);
+
+            for &b in rotations {
+                assert!(rotations.contains(&(a * b)));
+                assert_eq!((a * b).transform(v), a.transform(b.transform(v)));
+            }
+        }
+
+        let quarter = Rotation::QUARTER_Z;
+        assert_eq!(quarter.transform([1, 0, 0]), [0, 1, 0]);
+        assert_eq!(quarter * quarter * quarter * quarter, Rotation::IDENTITY);
+        assert_eq!(quarter * quarter * quarter, quarter.inverse());
+    }
+
+    #[test]
+    fn places_scanners() {
+        let scanners = parse_input(EXAMPLE).unwrap();
+        let map = reconstruct(&scanners, OVERLAP);
+
+        assert_eq!(
+            map.scanners
+                .iter()
+                .map(|s| s.unwrap().position)
+                .collect::<Vec<_>>(),
+            [
+                [0, 0, 0],
+                [68, -1246, -43],
+                [1105, -1205, 1229],
+                [-92, -2380, -20],
+                [-20, -1133, 1061]
+            ]
+        );
+        assert_eq!(map.scanners[0].unwrap().rotation, Rotation::IDENTITY);
+
+        // Every reading lands on a beacon of the map once placed
+        for (placement, readings) in map.scanners.iter().zip(&scanners) {
+            for &reading in readings {
+                let beacon = placement.unwrap().locate(reading);
+                assert!(map.beacons.binary_search(&beacon).is_ok());
+            }
+        }
+
+        assert_eq!(map.beacons.len(), 79);
+        assert_eq!(map.beacons.first(), Some(&[-892, 524, 684]));
+        assert_eq!(map.beacons.last(), Some(&[1994, -1805, 1792]));
+    }
+
+    #[test]
+    fn places_scanners_with_fewer_beacons_in_common() {
+        let scanners = parse_input(EXAMPLE).unwrap();
+        let map = reconstruct(&scanners, OVERLAP);
+        let placement = map.scanners[1].unwrap();
+
+        // Scanners 0 and 1 see exactly 12 beacons in common, drop 3 of them from scanner 1
+        let mut seen = 0;
+        let noisy = vec![
+            scanners[0].clone(),
+            scanners[1]
+                .iter()
+                .copied()
+                .filter(|&reading| {
+                    let common = scanners[0].contains(&placement.locate(reading));
+                    seen += common as usize;
+                    !common || seen > 3
+                })
+                .collect(),
+        ];
+
+        assert_eq!(reconstruct(&noisy, OVERLAP).scanners[1], None);
+        assert_eq!(reconstruct(&noisy, 9).scanners[1], Some(placement));
+        assert_eq!(reconstruct(&noisy, 9).beacons.len(), 25 + 22 - 9);
     }
 }
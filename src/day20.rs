@@ -1,100 +1,119 @@
-use std::collections::HashSet;
-
-pub fn parse_input(input: &str) -> (Vec<bool>, HashSet<(i32, i32)>) {
-    let (template, input) = input.split_once("\n\n").unwrap();
-
-    let template = template.bytes().map(|b| b == b'#').collect();
-
-    let input = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.bytes().enumerate().filter_map(move |(x, b)| {
-                if b == b'#' {
-                    Some((x as i32, y as i32))
-                } else {
-                    None
-                }
-            })
-        })
-        .collect();
-
-    (template, input)
+use nom::{
+    branch::alt, character::complete::char, combinator::value, multi::many1,
+    sequence::separated_pair,
+};
+
+use crate::{
+    parse::{self, blank_line, expect, grid, Expected, IResult},
+    Grid, ParseError, Solution,
+};
+
+pub type Image = Grid<bool>;
+
+pub fn parse_input(input: &str) -> Result<(Vec<bool>, Image), ParseError> {
+    parse::run(
+        Solver::DAY,
+        input,
+        separated_pair(algorithm, blank_line, grid(pixel)),
+    )
 }
 
-pub fn part_1(input: (Vec<bool>, HashSet<(i32, i32)>)) -> usize {
+fn algorithm(input: &str) -> IResult<'_, Vec<bool>> {
+    let (rest, template) = many1(pixel)(input)?;
+
+    if template.len() != 512 {
+        return Err(parse::fail(
+            input,
+            Expected::Description("a 512 pixel enhancement algorithm"),
+        ));
+    }
+
+    Ok((rest, template))
+}
+
+fn pixel(input: &str) -> IResult<'_, bool> {
+    expect(
+        Expected::OneOf(&["#", "."]),
+        alt((value(true, char('#')), value(false, char('.')))),
+    )(input)
+}
+
+pub fn part_1(input: &(Vec<bool>, Image)) -> usize {
     solve(input, 2)
 }
 
-pub fn part_2(input: (Vec<bool>, HashSet<(i32, i32)>)) -> usize {
+pub fn part_2(input: &(Vec<bool>, Image)) -> usize {
     solve(input, 50)
 }
 
-pub fn solve((template, mut image): (Vec<bool>, HashSet<(i32, i32)>), iterations: usize) -> usize {
-    let (mut x_min, mut x_max, mut y_min, mut y_max) = image.iter().fold(
-        (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
-        |(x_min, x_max, y_min, y_max), &(x, y)| {
-            (x_min.min(x), x_max.max(x), y_min.min(y), y_max.max(y))
-        },
-    );
+pub fn solve((template, image): &(Vec<bool>, Image), iterations: usize) -> usize {
+    let mut image = image.clone();
 
-    let mut outer_rim = 0;
+    // Pixels outside of the image are all dark or all lit
+    let mut outer = false;
 
     for _ in 0..iterations {
-        let mut output =
-            HashSet::with_capacity(((y_max - y_min + 3) * (x_max - x_min + 3)) as usize);
-
-        for y in y_min - 1..=y_max + 1 {
-            for x in x_min - 1..=x_max + 1 {
-                let idx = adjacent((x, y)).iter().fold(0, |idx, &(x, y)| {
-                    let lsb = if x < x_min || x > x_max || y < y_min || y > y_max {
-                        outer_rim
-                    } else {
-                        image.contains(&(x, y)) as i32
-                    };
-
-                    (idx << 1) | lsb
+        // Every step lights up pixels at most one away from the image, so it grows on each side
+        image = Image::from_fn(image.width() + 2, image.height() + 2, |pos| {
+            let idx = (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                .fold(0, |idx, (dx, dy)| {
+                    let lit = image
+                        .offset(pos, (dx - 1, dy - 1))
+                        .map_or(outer, |pos| image[pos]);
+
+                    (idx << 1) | lit as usize
                 });
 
-                if template[idx as usize] {
-                    output.insert((x, y));
-                }
-            }
-        }
+            template[idx]
+        });
 
-        image = output;
-        outer_rim ^= 1;
-
-        x_min -= 1;
-        x_max += 1;
-        y_min -= 1;
-        y_max += 1;
+        // Pixels outside of the image get enhanced together
+        outer = template[if outer { 511 } else { 0 }];
     }
 
-    image.len()
+    image.cells().iter().filter(|&&lit| lit).count()
 }
 
-fn adjacent((x, y): (i32, i32)) -> [(i32, i32); 9] {
-    [
-        (x - 1, y - 1),
-        (x, y - 1),
-        (x + 1, y - 1),
-        (x - 1, y),
-        (x, y),
-        (x + 1, y),
-        (x - 1, y + 1),
-        (x, y + 1),
-        (x + 1, y + 1),
-    ]
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 20;
+
+    type Input<'a> = (Vec<bool>, Image);
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
 }
 
+#[cfg(test)]
+const EXAMPLE: &str = "\
+..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+
+#..#.
+#....
+##..#
+..#..
+..###";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day20.txt"))),
-        5081
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        35
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day20.txt"))),
-        15088
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        3351
     }
 }
@@ -1,6 +1,11 @@
 use std::mem::swap;
 
-use itertools::Itertools;
+use nom::sequence::separated_pair;
+
+use crate::{
+    parse::{self, newline, number, token, Expected, IResult},
+    ParseError, Solution,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Universe {
@@ -15,12 +20,30 @@ impl Universe {
     }
 }
 
-pub fn parse_input(input: &str) -> (u32, u32) {
-    input
-        .lines()
-        .map(|l| l.rsplit_once(' ').unwrap().1.parse().unwrap())
-        .collect_tuple()
-        .unwrap()
+pub fn parse_input(input: &str) -> Result<(u32, u32), ParseError> {
+    parse::run(
+        Solver::DAY,
+        input,
+        separated_pair(
+            player("Player 1 starting position:"),
+            newline,
+            player("Player 2 starting position:"),
+        ),
+    )
+}
+
+fn player<'a>(prefix: &'static str) -> impl FnMut(&'a str) -> IResult<'a, u32> {
+    move |input| {
+        let (input, _) = token(prefix)(input)?;
+
+        match number(input)? {
+            (rest, n @ 1..=10) => Ok((rest, n)),
+            _ => Err(parse::fail(
+                input,
+                Expected::Description("a position between 1 and 10"),
+            )),
+        }
+    }
 }
 
 pub fn part_1(input: (u32, u32)) -> u32 {
@@ -105,13 +128,40 @@ fn dirac_game(cache: &mut Vec<Option<(u64, u64)>>, universe: Universe) -> (u64,
     wins
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 21;
+
+    type Input<'a> = (u32, u32);
+    type Part1 = u32;
+    type Part2 = u64;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(*input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(*input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+Player 1 starting position: 4
+Player 2 starting position: 8";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day21.txt"))),
-        432450
+        part_1(parse_input(EXAMPLE).unwrap()),
+        739785
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day21.txt"))),
-        138508043837521
+        part_2(parse_input(EXAMPLE).unwrap()),
+        444356092776315
     }
 }
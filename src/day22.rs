@@ -1,6 +1,17 @@
-use itertools::Itertools;
+use std::{collections::HashMap, ops::RangeInclusive};
 
-#[derive(Debug, Clone, Copy)]
+use nom::{
+    branch::alt,
+    combinator::value,
+    sequence::{preceded, separated_pair, tuple},
+};
+
+use crate::{
+    parse::{self, expect, lines, number, token, Expected, IResult},
+    ParseError, Solution,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Range(i64, i64);
 
 impl Range {
@@ -16,26 +27,14 @@ impl Range {
         self.0 <= other.1 && self.1 >= other.0
     }
 
-    pub fn split(&self, other: &Self) -> Vec<Range> {
-        if other.0 <= self.0 {
-            if other.1 >= self.1 {
-                vec![*self]
-            } else {
-                vec![Range(self.0, other.1), Range(other.1 + 1, self.1)]
-            }
-        } else if other.1 >= self.1 {
-            vec![Range(self.0, other.0 - 1), Range(other.0, self.1)]
-        } else {
-            vec![
-                Range(self.0, other.0 - 1),
-                Range(other.0, other.1),
-                Range(other.1 + 1, self.1),
-            ]
-        }
+    /// The part of both ranges, if they overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.intersects(other)
+            .then(|| Range(self.0.max(other.0), self.1.min(other.1)))
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Cuboid {
     x: Range,
     y: Range,
@@ -43,6 +42,21 @@ pub struct Cuboid {
 }
 
 impl Cuboid {
+    /// The cuboid spanning the given ranges of coordinates, unless one of them is empty.
+    pub fn new(
+        x: RangeInclusive<i64>,
+        y: RangeInclusive<i64>,
+        z: RangeInclusive<i64>,
+    ) -> Option<Self> {
+        let range = |r: RangeInclusive<i64>| (!r.is_empty()).then(|| Range(*r.start(), *r.end()));
+
+        Some(Cuboid {
+            x: range(x)?,
+            y: range(y)?,
+            z: range(z)?,
+        })
+    }
+
     pub fn volume(&self) -> i64 {
         self.x.length() * self.y.length() * self.z.length()
     }
@@ -55,99 +69,244 @@ impl Cuboid {
         self.x.intersects(&other.x) && self.y.intersects(&other.y) && self.z.intersects(&other.z)
     }
 
-    pub fn subtract(&self, other: &Self) -> Vec<Cuboid> {
-        if !self.intersects(other) {
-            return vec![*self];
-        }
+    /// The part of both cuboids, if they overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Some(Cuboid {
+            x: self.x.intersection(&other.x)?,
+            y: self.y.intersection(&other.y)?,
+            z: self.z.intersection(&other.z)?,
+        })
+    }
+}
 
-        let xs = self.x.split(&other.x);
-        let ys = self.y.split(&other.y);
-        let zs = self.z.split(&other.z);
+pub type Instruction = (bool, Cuboid);
+
+/// The cubes of the reactor that are on, as cuboids that count either for or against them.
+/// Switching a cuboid cancels out its overlap with every cuboid counted so far, and turning it
+/// on then adds it as a whole.
+#[derive(Debug, Clone, Default)]
+pub struct Reactor {
+    /// How many times each cuboid counts, which may be negative.
+    cuboids: HashMap<Cuboid, i64>,
+    lit: i64,
+}
 
-        let mut res = vec![];
+impl Reactor {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        for &x in &xs {
-            for &y in &ys {
-                for &z in &zs {
-                    if self.contains(x.0, y.0, z.0) && !other.contains(x.0, y.0, z.0) {
-                        res.push(Cuboid { x, y, z });
-                    }
-                }
+    /// Switches the cubes of a cuboid on or off, and returns how many cubes are on afterwards.
+    pub fn apply(&mut self, (on, cuboid): Instruction) -> i64 {
+        let mut changes = HashMap::<_, i64>::new();
+
+        for (other, &count) in &self.cuboids {
+            if let Some(overlap) = cuboid.intersection(other) {
+                *changes.entry(overlap).or_default() -= count;
             }
         }
+        if on {
+            *changes.entry(cuboid).or_default() += 1;
+        }
+
+        for (cuboid, change) in changes {
+            self.lit += cuboid.volume() * change;
+            *self.cuboids.entry(cuboid).or_default() += change;
+        }
+
+        // Cuboids that cancel out don't need to be intersected anymore
+        self.cuboids.retain(|_, count| *count != 0);
+
+        self.lit
+    }
 
-        res
+    /// Number of cubes that are on.
+    pub fn lit(&self) -> i64 {
+        self.lit
     }
 }
 
-pub type Instruction = (bool, Cuboid);
+pub fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse::run(Solver::DAY, input, lines(instruction))
+}
 
-pub fn parse_input(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(|l| {
-            let (command, coords) = l.split_once(' ').unwrap();
-            let (x, y, z) = coords
-                .split(',')
-                .map(|c| {
-                    let (low, high) = c.split_once("..").unwrap();
-                    Range(low[2..].parse().unwrap(), high.parse().unwrap())
-                })
-                .collect_tuple()
-                .unwrap();
-
-            (command == "on", Cuboid { x, y, z })
-        })
-        .collect()
+fn instruction(input: &str) -> IResult<'_, Instruction> {
+    let (input, on) = expect(
+        Expected::OneOf(&["on", "off"]),
+        alt((value(true, token("on")), value(false, token("off")))),
+    )(input)?;
+
+    let (rest, (x, _, y, _, z)) = tuple((
+        preceded(token("x="), range),
+        token(","),
+        preceded(token("y="), range),
+        token(","),
+        preceded(token("z="), range),
+    ))(input)?;
+
+    Ok((rest, (on, Cuboid { x, y, z })))
 }
 
-pub fn part_1(instructions: Vec<Instruction>) -> i64 {
-    let mut cuboids = Vec::<Cuboid>::new();
+fn range(input: &str) -> IResult<'_, Range> {
+    let (rest, (low, high)) = separated_pair(number, token(".."), number)(input)?;
 
-    for (on, cuboid) in instructions.into_iter().take_while(|(_, c)| {
-        c.x.0 >= -50 && c.x.1 <= 50 && c.y.0 >= -50 && c.y.1 <= 50 && c.z.0 >= -50 && c.z.1 <= 50
-    }) {
-        cuboids = cuboids
-            .iter()
-            .map(|c| c.subtract(&cuboid))
-            .flatten()
-            .collect();
+    if low > high {
+        return Err(parse::fail(
+            input,
+            Expected::Description("a range that doesn't end before it starts"),
+        ));
+    }
 
-        if on {
-            cuboids.push(cuboid);
+    Ok((rest, Range(low, high)))
+}
+
+/// The part of the reactor the initialization procedure is about.
+const INITIALIZATION_REGION: Cuboid = Cuboid {
+    x: Range(-50, 50),
+    y: Range(-50, 50),
+    z: Range(-50, 50),
+};
+
+pub fn part_1(instructions: &[Instruction]) -> i64 {
+    let mut reactor = Reactor::new();
+
+    for &(on, cuboid) in instructions {
+        if let Some(cuboid) = cuboid.intersection(&INITIALIZATION_REGION) {
+            reactor.apply((on, cuboid));
         }
+    }
+
+    reactor.lit()
+}
+
+pub fn part_2(instructions: &[Instruction]) -> i64 {
+    let mut reactor = Reactor::new();
 
-        dbg!(&cuboids);
+    for &instruction in instructions {
+        reactor.apply(instruction);
     }
 
-    cuboids.iter().map(Cuboid::volume).sum()
+    reactor.lit()
 }
 
-pub fn part_2(instructions: Vec<Instruction>) -> i64 {
-    let mut cuboids = Vec::<Cuboid>::new();
+pub struct Solver;
 
-    for (on, cuboid) in instructions {
-        cuboids = cuboids
-            .iter()
-            .map(|c| c.subtract(&cuboid))
-            .flatten()
-            .collect();
+impl crate::Solution for Solver {
+    const DAY: u8 = 22;
 
-        if on {
-            cuboids.push(cuboid);
-        }
+    type Input<'a> = Vec<Instruction>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
     }
 
-    cuboids.iter().map(Cuboid::volume).sum()
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
 }
 
+#[cfg(test)]
+const SMALL: &str = "\
+on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10";
+
+#[cfg(test)]
+const LARGE: &str = "\
+on x=-20..26,y=-36..17,z=-47..7
+on x=-20..33,y=-21..23,z=-26..28
+on x=-22..28,y=-29..23,z=-38..16
+on x=-46..7,y=-6..46,z=-50..-1
+on x=-49..1,y=-3..46,z=-24..28
+on x=2..47,y=-22..22,z=-23..27
+on x=-27..23,y=-28..26,z=-21..29
+on x=-39..5,y=-6..47,z=-3..44
+on x=-30..21,y=-8..43,z=-13..34
+on x=-22..26,y=-27..20,z=-29..19
+off x=-48..-32,y=26..41,z=-47..-37
+on x=-12..35,y=6..50,z=-50..-2
+off x=-48..-32,y=-32..-16,z=-15..-5
+on x=-18..26,y=-33..15,z=-7..46
+off x=-40..-22,y=-38..-28,z=23..41
+on x=-16..35,y=-41..10,z=-47..6
+off x=-32..-23,y=11..30,z=-14..3
+on x=-49..-5,y=-3..45,z=-29..18
+off x=18..30,y=-20..-8,z=-3..13
+on x=-41..9,y=-7..43,z=-33..15
+on x=-54112..-39298,y=-85059..-49293,z=-27449..7877
+on x=967..23432,y=45373..81175,z=27513..53682";
+
 crate::solutions! {
-    p1 => {
-        part_1(parse_input(include_str!("../inputs/day22.txt"))),
-        570915
+    p1_small => {
+        part_1(&parse_input(SMALL).unwrap()),
+        39
+    },
+    p1_large => {
+        part_1(&parse_input(LARGE).unwrap()),
+        590784
     },
-    p2 => {
-        part_2(parse_input(include_str!("../inputs/day22.txt"))),
-        1268313839428137
+    p2_small => {
+        part_2(&parse_input(SMALL).unwrap()),
+        39
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_reversed_ranges() {
+        assert_eq!(
+            parse_input("on x=5..1,y=0..0,z=0..0")
+                .unwrap_err()
+                .to_string(),
+            "day 22, line 1, column 6: expected a range that doesn't end before it starts"
+        );
+        assert_eq!(part_2(&parse_input("on x=1..1,y=0..0,z=-2..0").unwrap()), 3);
+    }
+
+    #[test]
+    fn partial_volumes() {
+        let mut reactor = Reactor::new();
+
+        let volumes = parse_input(SMALL)
+            .unwrap()
+            .into_iter()
+            .map(|instruction| reactor.apply(instruction))
+            .collect::<Vec<_>>();
+
+        assert_eq!(volumes, [27, 46, 38, 39]);
+        assert_eq!(reactor.lit(), 39);
+    }
+
+    #[test]
+    fn intersections() {
+        let a = Cuboid::new(0..=9, 0..=9, 0..=9).unwrap();
+
+        assert_eq!(
+            a.intersection(&Cuboid::new(5..=20, -5..=2, 9..=9).unwrap()),
+            Cuboid::new(5..=9, 0..=2, 9..=9)
+        );
+        assert_eq!(
+            a.intersection(&Cuboid::new(10..=20, 0..=9, 0..=9).unwrap()),
+            None
+        );
+        assert_eq!(Cuboid::new(0..=9, RangeInclusive::new(5, 1), 0..=9), None);
+
+        // Switching the same cuboid over and over leaves nothing behind to intersect with
+        let mut reactor = Reactor::new();
+        for on in [true, true, false, true, false] {
+            reactor.apply((on, a));
+        }
+        assert_eq!(reactor.lit(), 0);
+        assert!(reactor.cuboids.is_empty());
     }
 }
@@ -7,6 +7,18 @@ use std::{
 
 use itertools::Itertools;
 
+use nom::{
+    character::complete::{char, satisfy, space0},
+    combinator::map,
+    multi::{count, many1},
+    sequence::{delimited, preceded, terminated, tuple},
+};
+
+use crate::{
+    parse::{self, expect, newline, Expected, IResult},
+    ParseError, Solution,
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Spot {
     Hallway(usize),
@@ -59,7 +71,7 @@ impl Map {
             }
 
             // Find a free spot
-            if let Some((spot, _)) = room.iter().rev().find_position(|&&r| r == None) {
+            if let Some((spot, _)) = room.iter().rev().find_position(|r| r.is_none()) {
                 let spot = room.len() - spot - 1;
 
                 // Do not move into a room unless the rest of the room is already organized
@@ -120,7 +132,7 @@ impl Eq for Configuration {}
 
 impl PartialOrd for Configuration {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        self.score.partial_cmp(&other.score)
+        Some(self.cmp(other))
     }
 }
 
@@ -137,28 +149,74 @@ impl Hash for Configuration {
     }
 }
 
-pub fn parse_input(input: &str) -> Map {
-    let mut lines = input.lines().skip(2);
-
-    let front = lines.next().unwrap().as_bytes();
-    let back = lines.next().unwrap().as_bytes();
-
-    Map {
+pub fn parse_input(input: &str) -> Result<Map, ParseError> {
+    let hallway = delimited(
+        char('#'),
+        expect(
+            Expected::Description("an empty hallway"),
+            count(char('.'), 11),
+        ),
+        char('#'),
+    );
+
+    let (front, back) = parse::run(
+        Solver::DAY,
+        input,
+        delimited(
+            tuple((wall, newline, hallway, newline)),
+            tuple((terminated(side_rooms, newline), side_rooms)),
+            tuple((newline, wall)),
+        ),
+    )?;
+
+    Ok(Map {
         rooms: [
-            vec![Some(front[3] - b'A'), Some(back[3] - b'A')],
-            vec![Some(front[5] - b'A'), Some(back[5] - b'A')],
-            vec![Some(front[7] - b'A'), Some(back[7] - b'A')],
-            vec![Some(front[9] - b'A'), Some(back[9] - b'A')],
+            vec![front[0], back[0]],
+            vec![front[1], back[1]],
+            vec![front[2], back[2]],
+            vec![front[3], back[3]],
         ],
         hallway: [None; 11],
-    }
+    })
 }
 
-pub fn part_1(map: Map) -> usize {
-    solve(map)
+fn wall(input: &str) -> IResult<'_, Vec<char>> {
+    preceded(space0, many1(char('#')))(input)
+}
+
+fn side_rooms(input: &str) -> IResult<'_, [Option<u8>; 4]> {
+    let (rest, (_, a, _, b, _, c, _, d, _)) = tuple((
+        wall,
+        amphipod,
+        char('#'),
+        amphipod,
+        char('#'),
+        amphipod,
+        char('#'),
+        amphipod,
+        wall,
+    ))(input)?;
+
+    Ok((rest, [a, b, c, d]))
+}
+
+fn amphipod(input: &str) -> IResult<'_, Option<u8>> {
+    map(
+        expect(
+            Expected::OneOf(&["A", "B", "C", "D"]),
+            satisfy(|c| ('A'..='D').contains(&c)),
+        ),
+        |c| Some(c as u8 - b'A'),
+    )(input)
+}
+
+pub fn part_1(map: &Map) -> usize {
+    solve(map.clone())
 }
 
-pub fn part_2(mut map: Map) -> usize {
+pub fn part_2(map: &Map) -> usize {
+    let mut map = map.clone();
+
     map.rooms[0].insert(1, Some(3));
     map.rooms[0].insert(2, Some(3));
     map.rooms[1].insert(1, Some(2));
@@ -204,13 +262,43 @@ fn solve(map: Map) -> usize {
     unreachable!()
 }
 
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 23;
+
+    type Input<'a> = Map;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "\
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day23.txt"))),
-        18051
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        12521
     },
     p2 => {
-        part_2(parse_input(include_str!("../inputs/day23.txt"))),
-        50245
+        part_2(&parse_input(EXAMPLE).unwrap()),
+        44169
     }
 }
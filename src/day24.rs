@@ -1,6 +1,17 @@
 use std::collections::HashSet;
 
-use itertools::Itertools;
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{one_of, space1},
+    combinator::{consumed, map},
+    sequence::preceded,
+};
+
+use crate::{
+    parse::{self, expect, lines, number, Expected, IResult},
+    ParseError, Solution,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Chunk {
@@ -9,46 +20,113 @@ pub struct Chunk {
     div: i64,
 }
 
-pub fn parse_input(input: &str) -> Vec<Chunk> {
-    input
-        .lines()
-        .chunks(18)
-        .into_iter()
-        .map(|chunk| {
-            let instrs = chunk.collect_vec();
-
-            let div = instrs[4]
-                .split_ascii_whitespace()
-                .nth(2)
-                .unwrap()
-                .parse()
-                .unwrap();
-
-            let n = instrs[5]
-                .split_ascii_whitespace()
-                .nth(2)
-                .unwrap()
-                .parse()
-                .unwrap();
-
-            let m = instrs[15]
-                .split_ascii_whitespace()
-                .nth(2)
-                .unwrap()
-                .parse()
-                .unwrap();
-
-            Chunk { n, m, div }
-        })
-        .collect()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    Variable(char),
+    Number(i64),
 }
 
-pub fn part_1(chunks: Vec<Chunk>) -> i64 {
-    solve(&mut HashSet::new(), &chunks, 13, 0, (1..=9).rev()).unwrap()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Instruction<'a> {
+    op: &'a str,
+    a: char,
+    b: Option<Operand>,
 }
 
-pub fn part_2(chunks: Vec<Chunk>) -> i64 {
-    solve(&mut HashSet::new(), &chunks, 13, 0, 1..=9).unwrap()
+pub fn parse_input(input: &str) -> Result<Vec<Chunk>, ParseError> {
+    parse::run(Solver::DAY, input, |input| {
+        let (rest, program) = lines(consumed(instruction))(input)?;
+
+        // Each digit of the model number is processed by a block starting with an `inp`
+        let mut blocks: Vec<Vec<_>> = Vec::new();
+        for (line, instr) in program {
+            if instr.op == "inp" {
+                blocks.push(Vec::new());
+            }
+
+            match blocks.last_mut() {
+                Some(block) => block.push((line, instr)),
+                None => return Err(parse::fail(line, Expected::Token("inp"))),
+            }
+        }
+
+        if blocks.len() != 14 {
+            return Err(parse::fail(
+                rest,
+                Expected::Description("14 digits worth of instructions"),
+            ));
+        }
+
+        let chunks = blocks
+            .iter()
+            .map(|block| {
+                // The last instruction of the block with the given shape and a constant operand
+                let constant = |op, a, expected| {
+                    block
+                        .iter()
+                        .rev()
+                        .find_map(|(_, instr)| match *instr {
+                            Instruction {
+                                b: Some(Operand::Number(n)),
+                                ..
+                            } if instr.op == op && instr.a == a => Some(n),
+                            _ => None,
+                        })
+                        .ok_or_else(|| parse::fail(block[0].0, Expected::Token(expected)))
+                };
+
+                Ok(Chunk {
+                    n: constant("add", 'x', "add x")?,
+                    m: constant("add", 'y', "add y")?,
+                    div: constant("div", 'z', "div z")?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok((rest, chunks))
+    })
+}
+
+fn instruction(input: &str) -> IResult<'_, Instruction<'_>> {
+    let (input, op) = expect(
+        Expected::OneOf(&["inp", "add", "mul", "div", "mod", "eql"]),
+        alt((
+            tag("inp"),
+            tag("add"),
+            tag("mul"),
+            tag("div"),
+            tag("mod"),
+            tag("eql"),
+        )),
+    )(input)?;
+
+    let (input, a) = preceded(space1, variable)(input)?;
+
+    if op == "inp" {
+        return Ok((input, Instruction { op, a, b: None }));
+    }
+
+    let (rest, b) = preceded(
+        space1,
+        alt((
+            map(variable, Operand::Variable),
+            map(number, Operand::Number),
+        )),
+    )(input)?;
+
+    Ok((rest, Instruction { op, a, b: Some(b) }))
+}
+
+fn variable(input: &str) -> IResult<'_, char> {
+    expect(Expected::OneOf(&["w", "x", "y", "z"]), one_of("wxyz"))(input)
+}
+
+pub fn part_1(chunks: &[Chunk]) -> i64 {
+    solve(&mut HashSet::new(), chunks, 13, 0, (1..=9).rev()).unwrap()
+}
+
+pub fn part_2(chunks: &[Chunk]) -> i64 {
+    solve(&mut HashSet::new(), chunks, 13, 0, 1..=9).unwrap()
 }
 
 fn solve<I>(
@@ -97,13 +175,55 @@ fn compute_chunk(w: i64, z: i64, chunk: &Chunk) -> i64 {
     }
 }
 
-crate::solutions! {
-    p1 => {
-        part_1(parse_input(include_str!("../inputs/day24.txt"))),
-        79197919993985
-    },
-    p2 => {
-        part_2(parse_input(include_str!("../inputs/day24.txt"))),
-        13191913571211
+pub struct Solver;
+
+impl crate::Solution for Solver {
+    const DAY: u8 = 24;
+
+    type Input<'a> = Vec<Chunk>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2 {
+        part_2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_programs_other_than_monad() {
+        // The worked example only converts a single input to binary
+        let err = parse_input(
+            "\
+inp w
+add z w
+mod z 2
+div w 2
+add y w
+mod y 2
+div w 2
+add x w
+mod x 2
+div w 2
+mod w 2
+",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.expected,
+            Expected::Description("14 digits worth of instructions")
+        );
     }
 }
@@ -1,4 +1,9 @@
-use itertools::Itertools;
+use nom::{branch::alt, character::complete::char, combinator::value};
+
+use crate::{
+    parse::{self, expect, grid, Expected, IResult},
+    Grid, ParseError, Solution,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
@@ -6,78 +11,98 @@ pub enum Direction {
     South,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Grid {
-    cucumbers: Vec<Vec<Option<Direction>>>,
-    size: (usize, usize),
-}
+pub type SeaFloor = Grid<Option<Direction>>;
 
-pub fn parse_input(input: &str) -> Grid {
-    let lines = input.lines().collect_vec();
-    let size = (lines.len(), lines[0].len());
+pub fn parse_input(input: &str) -> Result<SeaFloor, ParseError> {
+    parse::run(Solver::DAY, input, grid(cucumber))
+}
 
-    let cucumbers = lines
-        .iter()
-        .map(|s| {
-            s.bytes()
-                .map(|b| match b {
-                    b'.' => None,
-                    b'>' => Some(Direction::East),
-                    b'v' => Some(Direction::South),
-                    _ => unreachable!(),
-                })
-                .collect_vec()
-        })
-        .collect_vec();
-
-    Grid { cucumbers, size }
+fn cucumber(input: &str) -> IResult<'_, Option<Direction>> {
+    expect(
+        Expected::OneOf(&[".", ">", "v"]),
+        alt((
+            value(None, char('.')),
+            value(Some(Direction::East), char('>')),
+            value(Some(Direction::South), char('v')),
+        )),
+    )(input)
 }
 
-pub fn part_1(mut grid: Grid) -> usize {
+pub fn part_1(floor: &SeaFloor) -> usize {
+    let mut floor = floor.clone();
+
     for iter in 1.. {
-        let mut next = grid.clone();
-
-        for row in 0..grid.size.0 {
-            for col in 0..grid.size.1 {
-                let idx = (col + 1) % grid.size.1;
-
-                if grid.cucumbers[row][col] == Some(Direction::East)
-                    && grid.cucumbers[row][idx] == None
-                {
-                    next.cucumbers[row][col] = None;
-                    next.cucumbers[row][idx] = Some(Direction::East);
-                }
-            }
+        let next = step(&step(&floor, Direction::East), Direction::South);
+
+        if next == floor {
+            return iter;
         }
 
-        let updated = next.clone();
+        floor = next;
+    }
+
+    unreachable!()
+}
 
-        for row in 0..updated.size.0 {
-            for col in 0..updated.size.1 {
-                let idx = (row + 1) % updated.size.0;
+/// Moves every cucumber of the `herd` that faces an empty spot, wrapping around the edges.
+fn step(floor: &SeaFloor, herd: Direction) -> SeaFloor {
+    let ahead = match herd {
+        Direction::East => (1, 0),
+        Direction::South => (0, 1),
+    };
 
-                if updated.cucumbers[row][col] == Some(Direction::South)
-                    && updated.cucumbers[idx][col] == None
-                {
-                    next.cucumbers[row][col] = None;
-                    next.cucumbers[idx][col] = Some(Direction::South);
-                }
-            }
-        }
+    let mut next = floor.clone();
 
-        if next == grid {
-            return iter;
+    for (pos, _) in floor
+        .iter()
+        .filter(|&(_, &cucumber)| cucumber == Some(herd))
+    {
+        let to = floor.wrapping_offset(pos, ahead);
+
+        if floor[to].is_none() {
+            next[pos] = None;
+            next[to] = Some(herd);
         }
+    }
+
+    next
+}
+
+pub struct Solver;
 
-        grid = next;
+impl crate::Solution for Solver {
+    const DAY: u8 = 25;
+
+    type Input<'a> = SeaFloor;
+    type Part1 = usize;
+    type Part2 = ();
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse_input(input)
     }
 
-    unreachable!()
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1 {
+        part_1(input)
+    }
+
+    fn part_2(_: &Self::Input<'_>) -> Self::Part2 {}
 }
 
+#[cfg(test)]
+const EXAMPLE: &str = "\
+v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>";
+
 crate::solutions! {
     p1 => {
-        part_1(parse_input(include_str!("../inputs/day25.txt"))),
-        278
+        part_1(&parse_input(EXAMPLE).unwrap()),
+        58
     }
 }
@@ -0,0 +1,285 @@
+//! Dense two-dimensional grids, as found in the map-like puzzle inputs.
+//!
+//! Positions are `(x, y)` pairs, with `x` growing to the right and `y` growing downwards from the
+//! top left corner. Grids are parsed from character maps with [`crate::parse::grid`].
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+/// A position on a grid, as `(x, y)`.
+pub type Pos = (usize, usize);
+
+/// Offsets of the 4 orthogonal neighbours of a cell, in reading order.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Offsets of the 8 neighbours of a cell, diagonals included, in reading order.
+pub const ALL_AROUND: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Lays out `cells` in rows of `width` cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cells don't fill a whole number of rows.
+    pub fn from_vec(cells: Vec<T>, width: usize) -> Self {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width),
+            "{} cells don't fit in rows of {}",
+            cells.len(),
+            width
+        );
+
+        Self {
+            height: cells.len() / width,
+            cells,
+            width,
+        }
+    }
+
+    /// Builds a grid by calling `f` on every position, in reading order.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Pos) -> T) -> Self {
+        Self {
+            cells: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(&mut f)
+                .collect(),
+            width,
+            height,
+        }
+    }
+
+    /// A grid of the given size with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of cells in the grid.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[self.cell_index(pos)])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            let i = self.cell_index(pos);
+            Some(&mut self.cells[i])
+        } else {
+            None
+        }
+    }
+
+    fn cell_index(&self, (x, y): Pos) -> usize {
+        y * self.width + x
+    }
+
+    /// All the positions of the grid, in reading order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// All the cells of the grid along with their positions, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    /// All the cells of the grid, in reading order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {} out of bounds", x);
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Applies `f` to every cell, keeping the layout.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// The position `delta` away from `pos`, if it's on the grid.
+    pub fn offset(&self, (x, y): Pos, (dx, dy): (isize, isize)) -> Option<Pos> {
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(pos).then_some(pos)
+    }
+
+    /// The position `delta` away from `pos`, wrapping around the edges of the grid.
+    pub fn wrapping_offset(&self, (x, y): Pos, (dx, dy): (isize, isize)) -> Pos {
+        let wrap =
+            |n: usize, d: isize, len: usize| (n as isize + d).rem_euclid(len as isize) as usize;
+
+        (wrap(x, dx, self.width), wrap(y, dy, self.height))
+    }
+
+    /// The orthogonal neighbours of `pos` that are on the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        ORTHOGONAL
+            .into_iter()
+            .filter_map(move |delta| self.offset(pos, delta))
+    }
+
+    /// The orthogonal and diagonal neighbours of `pos` that are on the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        ALL_AROUND
+            .into_iter()
+            .filter_map(move |delta| self.offset(pos, delta))
+    }
+
+    /// The orthogonal neighbours of `pos`, wrapping around the edges of the grid.
+    pub fn wrapping_neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        ORTHOGONAL
+            .into_iter()
+            .map(move |delta| self.wrapping_offset(pos, delta))
+    }
+
+    /// The orthogonal and diagonal neighbours of `pos`, wrapping around the edges of the grid.
+    pub fn wrapping_neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        ALL_AROUND
+            .into_iter()
+            .map(move |delta| self.wrapping_offset(pos, delta))
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        assert!(self.contains(pos), "{:?} out of bounds", pos);
+        &self.cells[self.cell_index(pos)]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        assert!(self.contains(pos), "{:?} out of bounds", pos);
+        let i = self.cell_index(pos);
+        &mut self.cells[i]
+    }
+}
+
+/// Renders the grid one row per line, with the cells of a row side by side.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u32> {
+        Grid::from_vec((1..=12).collect(), 4)
+    }
+
+    #[test]
+    fn lays_out_cells() {
+        let grid = grid();
+
+        assert_eq!((grid.width(), grid.height(), grid.len()), (4, 3, 12));
+        assert_eq!(grid[(1, 2)], 10);
+        assert_eq!(grid.get((4, 0)), None);
+        assert_eq!(grid.row(1), [5, 6, 7, 8]);
+        assert_eq!(grid.column(3).copied().collect::<Vec<_>>(), [4, 8, 12]);
+        assert_eq!(grid.columns().count(), 4);
+        assert_eq!(Grid::from_fn(4, 3, |(x, y)| (y * 4 + x + 1) as u32), grid);
+        assert_eq!(grid.to_string(), "1234\n5678\n9101112");
+    }
+
+    #[test]
+    fn finds_neighbours() {
+        let grid = grid();
+
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours8((3, 2)).count(), 3);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(
+            grid.wrapping_neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(0, 2), (3, 0), (1, 0), (0, 1)]
+        );
+        assert_eq!(grid.wrapping_neighbours8((3, 2)).count(), 8);
+        assert_eq!(grid.wrapping_offset((3, 2), (1, 1)), (0, 0));
+    }
+}
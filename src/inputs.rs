@@ -0,0 +1,80 @@
+//! Puzzle inputs, looked up at runtime.
+//!
+//! Inputs are personal to each Advent of Code account, so they aren't part of the crate. They
+//! live in a directory as `dayNN.txt` files (plus any extra inputs named after their day), along
+//! with the `answers.txt` file described in [`crate::answers`].
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable overriding the directory inputs are read from.
+pub const DIR_VAR: &str = "AOC21_INPUTS";
+
+/// A directory of puzzle inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Uses the directory named by [`DIR_VAR`] if it's set, or `dir` otherwise.
+    pub fn from_env_or(dir: impl Into<PathBuf>) -> Self {
+        match env::var_os(DIR_VAR) {
+            Some(dir) if !dir.is_empty() => Self::new(dir),
+            _ => Self::new(dir),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the input called `name`, without its `.txt` extension.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.txt", name))
+    }
+
+    pub fn read(&self, name: &str) -> io::Result<String> {
+        fs::read_to_string(self.path(name))
+    }
+
+    /// Reads the main input of the given day of the calendar.
+    pub fn day(&self, day: u8) -> io::Result<String> {
+        self.read(&day_name(day))
+    }
+}
+
+/// Reads inputs from `$AOC21_INPUTS`, or from `inputs` in the current directory.
+impl Default for Inputs {
+    fn default() -> Self {
+        Self::from_env_or("inputs")
+    }
+}
+
+/// Name of the main input of a day, e.g. `day07`.
+pub fn day_name(day: u8) -> String {
+    format!("day{:02}", day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_paths() {
+        let inputs = Inputs::new("somewhere");
+
+        assert_eq!(
+            inputs.path("day13-alice"),
+            Path::new("somewhere/day13-alice.txt")
+        );
+        assert_eq!(inputs.path(&day_name(7)), Path::new("somewhere/day07.txt"));
+        assert_eq!(inputs.day(7).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
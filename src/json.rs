@@ -0,0 +1,128 @@
+//! Machine-readable output of answers and timings, as JSON.
+//!
+//! Every part that was solved becomes one flat record, e.g.:
+//!
+//! ```json
+//! {"day": 1, "part": 1, "input": "inputs/day01.txt", "answer": 1529, "parse_time_ns": 158240, "solve_time_ns": 1480}
+//! ```
+//!
+//! Numeric answers are JSON numbers, textual ones (i.e. the letters of day 13) are strings and
+//! parts without an answer are `null`.
+
+use std::fmt::Write;
+
+use crate::{Answer, Part, Run};
+
+/// Quotes and escapes `s` as a JSON string.
+pub fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+
+    out
+}
+
+impl Answer {
+    pub fn to_json(&self) -> String {
+        match self {
+            Answer::Unsigned(n) => n.to_string(),
+            Answer::Signed(n) => n.to_string(),
+            Answer::Text(s) => string(s),
+            Answer::None => "null".to_string(),
+        }
+    }
+}
+
+/// One JSON object per part solved by `run`, on `input` for the given day.
+pub fn records(day: u8, input: &str, run: &Run) -> Vec<String> {
+    run.parts
+        .iter()
+        .map(|part| {
+            format!(
+                "{{\"day\": {}, \"part\": {}, \"input\": {}, \"answer\": {}, \"parse_time_ns\": {}, \"solve_time_ns\": {}}}",
+                day,
+                match part.part {
+                    Part::One => 1,
+                    Part::Two => 2,
+                },
+                string(input),
+                part.answer.to_json(),
+                run.parse_time.as_nanos(),
+                part.solve_time.as_nanos(),
+            )
+        })
+        .collect()
+}
+
+/// Lays out `records` as a JSON array, one record per line.
+pub fn array(records: impl IntoIterator<Item = String>) -> String {
+    let records = records.into_iter().collect::<Vec<_>>();
+
+    if records.is_empty() {
+        "[]".to_string()
+    } else {
+        format!("[\n  {}\n]", records.join(",\n  "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::PartRun;
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(string("# #\n\"a\\b\"\u{1}"), r##""# #\n\"a\\b\"\u0001""##);
+    }
+
+    #[test]
+    fn formats_every_answer_type() {
+        assert_eq!(Answer::from(7_usize).to_json(), "7");
+        assert_eq!(Answer::from(u64::MAX).to_json(), "18446744073709551615");
+        assert_eq!(Answer::from(-3_i32).to_json(), "-3");
+        assert_eq!(Answer::from("ABC".to_string()).to_json(), r#""ABC""#);
+        assert_eq!(Answer::from(()).to_json(), "null");
+    }
+
+    #[test]
+    fn formats_records() {
+        let run = Run {
+            parse_time: Duration::from_micros(5),
+            parts: vec![
+                PartRun {
+                    part: Part::One,
+                    answer: Answer::Unsigned(58),
+                    solve_time: Duration::from_nanos(42),
+                },
+                PartRun {
+                    part: Part::Two,
+                    answer: Answer::None,
+                    solve_time: Duration::ZERO,
+                },
+            ],
+        };
+
+        assert_eq!(
+            array(records(25, "inputs/day25.txt", &run)),
+            r#"[
+  {"day": 25, "part": 1, "input": "inputs/day25.txt", "answer": 58, "parse_time_ns": 5000, "solve_time_ns": 42},
+  {"day": 25, "part": 2, "input": "inputs/day25.txt", "answer": null, "parse_time_ns": 5000, "solve_time_ns": 0}
+]"#
+        );
+        assert_eq!(array(Vec::new()), "[]");
+    }
+}
@@ -1,3 +1,9 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+pub mod answers;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -23,6 +29,206 @@ pub mod day22;
 pub mod day23;
 pub mod day24;
 pub mod day25;
+pub mod grid;
+pub mod inputs;
+pub mod json;
+pub mod parse;
+pub mod runner;
+
+pub use grid::Grid;
+pub use inputs::Inputs;
+pub use parse::ParseError;
+
+/// A puzzle solution, tying together the parser and the two parts of a day.
+pub trait Solution {
+    /// Day of the calendar this solution belongs to, starting from 1.
+    const DAY: u8;
+
+    /// Parsed puzzle input, possibly borrowing from the raw input text.
+    type Input<'a>;
+
+    type Part1: Into<Answer>;
+    type Part2: Into<Answer>;
+
+    fn parse_input(input: &str) -> Result<Self::Input<'_>, ParseError>;
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Part1;
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Part2;
+}
+
+/// The answer to one part of a puzzle, whatever type the solver returned it as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Unsigned(u64),
+    Signed(i64),
+    Text(String),
+    /// The part has no puzzle of its own (i.e. the second half of day 25).
+    None,
+}
+
+macro_rules! answer_from {
+    ($variant:ident as $repr:ty => $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::$variant(n as $repr)
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(Unsigned as u64 => usize, u32, u64);
+answer_from!(Signed as i64 => i32, i64);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<()> for Answer {
+    fn from(_: ()) -> Self {
+        Answer::None
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Unsigned(n) => write!(f, "{}", n),
+            Answer::Signed(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::None => write!(f, "-"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+/// A type-erased [`Solution`], as stored in the [`DAYS`] registry.
+pub struct Day {
+    number: u8,
+    run: fn(&str, &[Part]) -> Result<Run, ParseError>,
+}
+
+impl Day {
+    const fn new<S: Solution>() -> Self {
+        Self {
+            number: S::DAY,
+            run: run::<S>,
+        }
+    }
+
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    /// Parses `input` once and solves the requested `parts` on it, in order.
+    pub fn run(&self, input: &str, parts: &[Part]) -> Result<Vec<Answer>, ParseError> {
+        Ok(self
+            .run_timed(input, parts)?
+            .parts
+            .into_iter()
+            .map(|part| part.answer)
+            .collect())
+    }
+
+    /// Like [`Day::run`], but also measures how long parsing and each part took.
+    pub fn run_timed(&self, input: &str, parts: &[Part]) -> Result<Run, ParseError> {
+        (self.run)(input, parts)
+    }
+}
+
+/// Answers to some parts of a day, along with the wall time spent on each step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub parse_time: Duration,
+    pub parts: Vec<PartRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartRun {
+    pub part: Part,
+    pub answer: Answer,
+    pub solve_time: Duration,
+}
+
+impl Run {
+    /// Time spent parsing and solving all the parts.
+    pub fn total_time(&self) -> Duration {
+        self.parse_time
+            + self
+                .parts
+                .iter()
+                .map(|part| part.solve_time)
+                .sum::<Duration>()
+    }
+}
+
+fn run<S: Solution>(input: &str, parts: &[Part]) -> Result<Run, ParseError> {
+    let start = Instant::now();
+    let input = S::parse_input(input)?;
+    let parse_time = start.elapsed();
+
+    let parts = parts
+        .iter()
+        .map(|&part| {
+            let start = Instant::now();
+            let answer = match part {
+                Part::One => S::part_1(&input).into(),
+                Part::Two => S::part_2(&input).into(),
+            };
+
+            PartRun {
+                part,
+                answer,
+                solve_time: start.elapsed(),
+            }
+        })
+        .collect();
+
+    Ok(Run { parse_time, parts })
+}
+
+/// All the solutions, sorted by day.
+pub static DAYS: [Day; 25] = [
+    Day::new::<day01::Solver>(),
+    Day::new::<day02::Solver>(),
+    Day::new::<day03::Solver>(),
+    Day::new::<day04::Solver>(),
+    Day::new::<day05::Solver>(),
+    Day::new::<day06::Solver>(),
+    Day::new::<day07::Solver>(),
+    Day::new::<day08::Solver>(),
+    Day::new::<day09::Solver>(),
+    Day::new::<day10::Solver>(),
+    Day::new::<day11::Solver>(),
+    Day::new::<day12::Solver>(),
+    Day::new::<day13::Solver>(),
+    Day::new::<day14::Solver>(),
+    Day::new::<day15::Solver>(),
+    Day::new::<day16::Solver>(),
+    Day::new::<day17::Solver>(),
+    Day::new::<day18::Solver>(),
+    Day::new::<day19::Solver>(),
+    Day::new::<day20::Solver>(),
+    Day::new::<day21::Solver>(),
+    Day::new::<day22::Solver>(),
+    Day::new::<day23::Solver>(),
+    Day::new::<day24::Solver>(),
+    Day::new::<day25::Solver>(),
+];
+
+/// Looks up the solution for the given day of the calendar.
+pub fn day(number: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
 
 #[macro_export]
 macro_rules! solutions {
@@ -39,3 +245,37 @@ macro_rules! solutions {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_sorted_by_day() {
+        for (i, day) in DAYS.iter().enumerate() {
+            assert_eq!(day.number() as usize, i + 1);
+        }
+        assert!(day(0).is_none());
+        assert!(day(26).is_none());
+    }
+
+    #[test]
+    fn registry_runs_parts_in_order() {
+        let answers = day(1).unwrap().run(
+            "199\n200\n208\n210\n200\n207\n240\n269\n260\n263",
+            &[Part::Two, Part::One],
+        );
+
+        assert_eq!(answers, Ok(vec![Answer::Unsigned(5), Answer::Unsigned(7)]));
+    }
+
+    #[test]
+    fn registry_reports_parse_errors() {
+        let err = day(1).unwrap().run("199\n2x0\n", &[Part::One]).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "day 1, line 2, column 2: expected end of line"
+        );
+    }
+}
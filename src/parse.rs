@@ -0,0 +1,354 @@
+use std::{fmt, str::FromStr};
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::{digit1, line_ending, one_of, satisfy, space0},
+    combinator::{opt, recognize},
+    error::{ErrorKind, FromExternalError, ParseError as NomParseError},
+    multi::many1,
+    sequence::pair,
+};
+
+use crate::Grid;
+
+/// Error returned when a puzzle input doesn't match the format a day expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    /// Line of the offending input, starting from 1.
+    pub line: usize,
+    /// Column of the offending input in characters, starting from 1.
+    pub column: usize,
+    pub expected: Expected,
+}
+
+/// What the parser was looking for when it gave up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    Number,
+    Digit,
+    HexDigit,
+    Char(char),
+    Token(&'static str),
+    OneOf(&'static [&'static str]),
+    Row(usize),
+    EndOfLine,
+    EndOfInput,
+    Description(&'static str),
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Number => write!(f, "a number"),
+            Expected::Digit => write!(f, "a digit"),
+            Expected::HexDigit => write!(f, "a hexadecimal digit"),
+            Expected::Char(c) => write!(f, "`{}`", c),
+            Expected::Token(token) => write!(f, "`{}`", token),
+            Expected::OneOf(tokens) => {
+                write!(f, "one of ")?;
+                for (i, token) in tokens.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "`{}`", token)?;
+                }
+                Ok(())
+            }
+            Expected::Row(width) => write!(f, "a row of {} cells", width),
+            Expected::EndOfLine => write!(f, "end of line"),
+            Expected::EndOfInput => write!(f, "end of input"),
+            Expected::Description(what) => write!(f, "{}", what),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: expected {}",
+            self.day, self.line, self.column, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Error type threaded through the `nom` parsers, pointing into the input being parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error<'a> {
+    pub at: &'a str,
+    pub expected: Expected,
+}
+
+pub type IResult<'a, T> = nom::IResult<&'a str, T, Error<'a>>;
+
+impl<'a> Error<'a> {
+    pub fn new(at: &'a str, expected: Expected) -> Self {
+        Self { at, expected }
+    }
+}
+
+impl<'a> NomParseError<&'a str> for Error<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        let expected = match kind {
+            ErrorKind::Digit => Expected::Digit,
+            ErrorKind::HexDigit => Expected::HexDigit,
+            ErrorKind::CrLf => Expected::EndOfLine,
+            ErrorKind::Eof => Expected::EndOfInput,
+            ErrorKind::Space | ErrorKind::MultiSpace => Expected::Description("whitespace"),
+            ErrorKind::Alpha => Expected::Description("a letter"),
+            _ => Expected::Description("valid input"),
+        };
+
+        Self::new(input, expected)
+    }
+
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn from_char(input: &'a str, c: char) -> Self {
+        Self::new(input, Expected::Char(c))
+    }
+
+    /// Of two failed alternatives, report the one that got further into the input.
+    fn or(self, other: Self) -> Self {
+        if other.at.len() < self.at.len() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<'a, E> FromExternalError<&'a str, E> for Error<'a> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, _: E) -> Self {
+        Self::from_error_kind(input, kind)
+    }
+}
+
+/// Returns an unrecoverable error pointing at `at`, for input that parses but makes no sense.
+pub fn fail(at: &str, expected: Expected) -> nom::Err<Error<'_>> {
+    nom::Err::Failure(Error::new(at, expected))
+}
+
+/// Runs `parser` on the whole of `input`, allowing trailing whitespace, and converts any error
+/// into a [`ParseError`] pointing at the offending line and column.
+pub fn run<'a, T, F>(day: u8, input: &'a str, mut parser: F) -> Result<T, ParseError>
+where
+    F: FnMut(&'a str) -> IResult<'a, T>,
+{
+    let err = match parser(input) {
+        Ok((rest, value)) if rest.trim().is_empty() => return Ok(value),
+        Ok((rest, _)) => Error::new(rest.trim_start(), Expected::EndOfInput),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e,
+        Err(nom::Err::Incomplete(_)) => Error::new(&input[input.len()..], Expected::EndOfInput),
+    };
+
+    let offset = (err.at.as_ptr() as usize)
+        .saturating_sub(input.as_ptr() as usize)
+        .min(input.len());
+
+    let before = &input[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    Err(ParseError {
+        day,
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        expected: err.expected,
+    })
+}
+
+/// Replaces the error of `parser` with `expected`, unless it failed past the start of its input.
+pub fn expect<'a, T, F>(expected: Expected, mut parser: F) -> impl FnMut(&'a str) -> IResult<'a, T>
+where
+    F: FnMut(&'a str) -> IResult<'a, T>,
+{
+    move |input| {
+        parser(input).map_err(|e| {
+            e.map(|e| {
+                if e.at.len() == input.len() {
+                    Error::new(input, expected.clone())
+                } else {
+                    e
+                }
+            })
+        })
+    }
+}
+
+/// Matches `t`, ignoring any horizontal whitespace around it.
+pub fn token<'a>(t: &'static str) -> impl FnMut(&'a str) -> IResult<'a, &'a str> {
+    move |input| {
+        let (input, _) = space0(input)?;
+        let (rest, tok) =
+            tag(t)(input).map_err(|e| e.map(|_: Error| Error::new(input, Expected::Token(t))))?;
+        let (rest, _) = space0(rest)?;
+
+        Ok((rest, tok))
+    }
+}
+
+/// Parses a decimal integer with an optional sign.
+pub fn number<T: FromStr>(input: &str) -> IResult<'_, T> {
+    let (rest, digits) = recognize(pair(opt(one_of("+-")), digit1))(input)
+        .map_err(|e| e.map(|_: Error| Error::new(input, Expected::Number)))?;
+
+    match digits.parse() {
+        Ok(n) => Ok((rest, n)),
+        Err(_) => Err(fail(input, Expected::Number)),
+    }
+}
+
+/// Parses a single decimal digit.
+pub fn digit(input: &str) -> IResult<'_, u32> {
+    let (rest, c) = expect(Expected::Digit, satisfy(|c| c.is_ascii_digit()))(input)?;
+    Ok((rest, c as u32 - '0' as u32))
+}
+
+/// Parses a single hexadecimal digit.
+pub fn hex_digit(input: &str) -> IResult<'_, u32> {
+    let (rest, c) = expect(Expected::HexDigit, satisfy(|c| c.is_ascii_hexdigit()))(input)?;
+    Ok((rest, c.to_digit(16).unwrap_or_default()))
+}
+
+/// Matches the end of a line, along with any trailing whitespace on it.
+pub fn newline(input: &str) -> IResult<'_, &str> {
+    expect(Expected::EndOfLine, recognize(pair(space0, line_ending)))(input)
+}
+
+/// Matches the end of a line followed by one or more blank lines.
+pub fn blank_line(input: &str) -> IResult<'_, &str> {
+    expect(
+        Expected::Description("a blank line"),
+        recognize(pair(newline, many1(newline))),
+    )(input)
+}
+
+/// Parses one `item` per line, stopping at the first blank line or at the end of the input.
+pub fn lines<'a, T, F>(mut item: F) -> impl FnMut(&'a str) -> IResult<'a, Vec<T>>
+where
+    F: FnMut(&'a str) -> IResult<'a, T>,
+{
+    move |mut input| {
+        let mut items = Vec::new();
+
+        loop {
+            let (rest, x) = item(input)?;
+            items.push(x);
+
+            match newline(rest) {
+                // Another line of the same block follows
+                Ok((next, _)) if !next.trim().is_empty() && newline(next).is_err() => input = next,
+                // A blank line or the end of the input
+                Ok(_) => return Ok((rest, items)),
+                Err(_) if rest.trim().is_empty() => return Ok((rest, items)),
+                Err(_) => return Err(fail(rest.trim_start(), Expected::EndOfLine)),
+            }
+        }
+    }
+}
+
+/// Parses one `item` per block of lines, with blocks separated by blank lines.
+pub fn sections<'a, T, F>(mut item: F) -> impl FnMut(&'a str) -> IResult<'a, Vec<T>>
+where
+    F: FnMut(&'a str) -> IResult<'a, T>,
+{
+    move |mut input| {
+        let mut items = Vec::new();
+
+        loop {
+            let (rest, x) = item(input)?;
+            items.push(x);
+
+            match blank_line(rest) {
+                Ok((next, _)) if !next.trim().is_empty() => input = next,
+                _ => return Ok((rest, items)),
+            }
+        }
+    }
+}
+
+/// Parses a rectangular map of `cell`s, one character or more each, into a [`Grid`].
+pub fn grid<'a, T, F>(mut cell: F) -> impl FnMut(&'a str) -> IResult<'a, Grid<T>>
+where
+    F: FnMut(&'a str) -> IResult<'a, T>,
+{
+    move |input| {
+        let mut cells = Vec::new();
+        let mut width = None;
+
+        let (rest, _) = lines(|line| {
+            let (rest, row) = many1(&mut cell)(line)?;
+
+            match width {
+                None => width = Some(row.len()),
+                Some(width) if width != row.len() => return Err(fail(line, Expected::Row(width))),
+                _ => (),
+            }
+
+            cells.extend(row);
+            Ok((rest, ()))
+        })(input)?;
+
+        Ok((rest, Grid::from_vec(cells, width.unwrap_or_default())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::sequence::separated_pair;
+
+    use super::*;
+
+    fn pairs(input: &str) -> Result<Vec<(u32, u32)>, ParseError> {
+        run(7, input, lines(separated_pair(number, token(","), number)))
+    }
+
+    #[test]
+    fn locates_errors() {
+        let err = pairs("1,2\n3,x\n").unwrap_err();
+
+        assert_eq!(
+            err,
+            ParseError {
+                day: 7,
+                line: 2,
+                column: 3,
+                expected: Expected::Number,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "day 7, line 2, column 3: expected a number"
+        );
+
+        let err = pairs("1,2\n3,4 5\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+        assert_eq!(err.expected, Expected::EndOfLine);
+
+        let err = pairs("1,2\n\n3,4").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        assert_eq!(err.expected, Expected::EndOfInput);
+    }
+
+    #[test]
+    fn tolerates_whitespace() {
+        assert_eq!(pairs("1,2\r\n3 , 4  \r\n\r\n"), Ok(vec![(1, 2), (3, 4)]));
+    }
+
+    #[test]
+    fn rejects_ragged_grids() {
+        let err = run(9, "123\n45\n", grid(digit)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.expected, Expected::Row(3));
+
+        assert_eq!(
+            run(9, "12\r\n34", grid(digit)),
+            Ok(Grid::from_vec(vec![1, 2, 3, 4], 2))
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Running the whole calendar at once.
+
+use std::{
+    fmt, io,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{inputs, Inputs, ParseError, Part, Run, DAYS};
+
+/// Why a day couldn't be run.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+    /// Whether the day was skipped because its input doesn't exist, rather than failing.
+    pub fn is_missing_input(&self) -> bool {
+        matches!(self, Error::Io(e) if e.kind() == io::ErrorKind::NotFound)
+    }
+}
+
+/// The outcome of running every day of the calendar.
+#[derive(Debug)]
+pub struct Calendar {
+    /// One result per day, sorted by day.
+    pub days: Vec<(u8, Result<Run, Error>)>,
+    /// Wall time of the whole run, which is less than the sum of the days when run in parallel.
+    pub elapsed: Duration,
+}
+
+impl Calendar {
+    /// Time spent parsing and solving, summed over all the days that ran.
+    pub fn total_time(&self) -> Duration {
+        self.runs().map(|(_, run)| run.total_time()).sum()
+    }
+
+    /// The days that ran successfully.
+    pub fn runs(&self) -> impl Iterator<Item = (u8, &Run)> {
+        self.days
+            .iter()
+            .filter_map(|(day, run)| run.as_ref().ok().map(|run| (*day, run)))
+    }
+}
+
+/// Runs the requested `parts` of every day on its input from `inputs`, spreading the days over
+/// up to `jobs` threads.
+pub fn run_all(inputs: &Inputs, parts: &[Part], jobs: usize) -> Calendar {
+    let start = Instant::now();
+    let next = AtomicUsize::new(0);
+
+    let mut days = thread::scope(|s| {
+        let workers = (0..jobs.clamp(1, DAYS.len()))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+
+                    while let Some(day) = DAYS.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let run = inputs
+                            .day(day.number())
+                            .map_err(Error::Io)
+                            .and_then(|input| day.run_timed(&input, parts).map_err(Error::Parse));
+
+                        done.push((day.number(), run));
+                    }
+
+                    done
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    days.sort_by_key(|&(day, _)| day);
+
+    Calendar {
+        days,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Formats a [`Calendar`] as a table of timings per day and step, followed by the totals.
+pub struct Table<'a>(pub &'a Calendar);
+
+impl fmt::Display for Table<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = |d: Duration| format!("{:.2?}", d);
+
+        writeln!(
+            f,
+            "{:>3}  {:>10}  {:>10}  {:>10}  {:>10}",
+            "Day", "Parse", "Part 1", "Part 2", "Total"
+        )?;
+
+        for (day, run) in &self.0.days {
+            let run = match run {
+                Ok(run) => run,
+                Err(e) if e.is_missing_input() => {
+                    writeln!(f, "{:>3}  skipped: no {}.txt", day, inputs::day_name(*day))?;
+                    continue;
+                }
+                Err(e) => {
+                    writeln!(f, "{:>3}  error: {}", day, e)?;
+                    continue;
+                }
+            };
+
+            let part = |part| {
+                run.parts
+                    .iter()
+                    .find(|p| p.part == part)
+                    .map_or_else(|| "-".to_string(), |p| time(p.solve_time))
+            };
+
+            writeln!(
+                f,
+                "{:>3}  {:>10}  {:>10}  {:>10}  {:>10}",
+                day,
+                time(run.parse_time),
+                part(Part::One),
+                part(Part::Two),
+                time(run.total_time()),
+            )?;
+        }
+
+        let runs = || self.0.runs().map(|(_, run)| run);
+        let part = |part| {
+            runs()
+                .flat_map(|run| &run.parts)
+                .filter(|p| p.part == part)
+                .map(|p| p.solve_time)
+                .sum()
+        };
+
+        writeln!(
+            f,
+            "{:>3}  {:>10}  {:>10}  {:>10}  {:>10}",
+            "All",
+            time(runs().map(|run| run.parse_time).sum()),
+            time(part(Part::One)),
+            time(part(Part::Two)),
+            time(self.0.total_time())
+        )?;
+        write!(f, "Wall time: {}", time(self.0.elapsed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_missing_inputs() {
+        let calendar = run_all(&Inputs::new("no/such/dir"), &[Part::One], 4);
+
+        assert_eq!(calendar.days.len(), 25);
+        assert!(calendar
+            .days
+            .iter()
+            .zip(1..)
+            .all(|((day, run), n)| *day == n && run.as_ref().unwrap_err().is_missing_input()));
+        assert!(Table(&calendar)
+            .to_string()
+            .contains("25  skipped: no day25.txt"));
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    io::{self, Write},
+    thread,
+};
+
+use aoc21_rs::{
+    answers::{self, Answers},
+    Inputs, Part,
+};
+
+fn inputs() -> Inputs {
+    Inputs::from_env_or(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs"))
+}
+
+/// Reports a skipped check straight to stderr, bypassing the output capture of the test harness
+/// so that it shows up even though the test passes.
+fn skip(reason: String) {
+    io::stderr()
+        .write_all(format!("skipping {}\n", reason).as_bytes())
+        .unwrap();
+}
+
+#[test]
+fn inputs_match_expected_answers() {
+    let inputs = inputs();
+
+    let answers = match inputs.read("answers") {
+        Ok(text) => answers::parse(&text).unwrap(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return skip(format!(
+                "all inputs: {} not found",
+                inputs.path("answers").display()
+            ))
+        }
+        Err(e) => panic!("cannot read {}: {}", inputs.path("answers").display(), e),
+    };
+
+    let failures = thread::scope(|s| {
+        let checks = answers
+            .iter()
+            .map(|answers| s.spawn(|| check(&inputs, answers)))
+            .collect::<Vec<_>>();
+
+        checks
+            .into_iter()
+            .flat_map(|check| check.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+fn check(inputs: &Inputs, answers: &Answers) -> Vec<String> {
+    let input = match inputs.read(&answers.input) {
+        Ok(input) => input,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            skip(format!(
+                "{}: {} not found",
+                answers.input,
+                inputs.path(&answers.input).display()
+            ));
+            return Vec::new();
+        }
+        Err(e) => return vec![format!("{}: {}", answers.input, e)],
+    };
+
+    let parts = [Part::One, Part::Two]
+        .into_iter()
+        .filter(|&part| answers.expected(part).is_some())
+        .collect::<Vec<_>>();
+
+    let actual = match aoc21_rs::day(answers.day).unwrap().run(&input, &parts) {
+        Ok(actual) => actual,
+        Err(e) => return vec![format!("{}: {}", answers.input, e)],
+    };
+
+    parts
+        .iter()
+        .zip(actual)
+        .filter(|(&part, actual)| answers.expected(part) != Some(&actual.to_string()))
+        .map(|(&part, actual)| {
+            format!(
+                "{} {:?}: expected {}, got {}",
+                answers.input,
+                part,
+                answers.expected(part).unwrap(),
+                actual
+            )
+        })
+        .collect()
+}